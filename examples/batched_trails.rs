@@ -0,0 +1,82 @@
+//! Draw-call benchmark: 500 orbiting trails share one material. Press B to
+//! toggle `Trail::batched` and compare the frame time logged to the console,
+//! one draw call per trail against a single one for the whole batch.
+
+use bevy::diagnostic::{
+    EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin,
+};
+use bevy::prelude::*;
+use bevy_trail::{trail_material, Trail, TrailPlugin};
+
+const TRAIL_COUNT: usize = 500;
+
+#[derive(Component)]
+struct Orbit {
+    radius: f32,
+    speed: f32,
+    phase: f32,
+}
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins((
+            FrameTimeDiagnosticsPlugin,
+            EntityCountDiagnosticsPlugin,
+            LogDiagnosticsPlugin::default(),
+        ))
+        .add_plugins(TrailPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, (orbit, toggle_batching))
+        .run();
+}
+
+fn setup(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_xyz(0.0, 18.0, 18.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_rotation(Quat::from_euler(EulerRot::XYZ, -1.0, -0.4, 0.0)),
+        ..default()
+    });
+
+    let material = materials.add(trail_material(Color::srgba(0.3, 0.7, 1.0, 0.8)));
+    for i in 0..TRAIL_COUNT {
+        let t = i as f32 / TRAIL_COUNT as f32;
+        let mut trail = Trail::new(30, 30.0, 0.1, material.clone());
+        trail.batched = true;
+        commands.spawn((
+            trail,
+            Orbit {
+                radius: 2.0 + t * 10.0,
+                speed: 0.5 + (i % 7) as f32 * 0.1,
+                phase: t * std::f32::consts::TAU * 13.0,
+            },
+            TransformBundle::default(),
+        ));
+    }
+
+    info!("{TRAIL_COUNT} trails batched, press B to toggle batching");
+}
+
+fn orbit(time: Res<Time>, mut query: Query<(&Orbit, &mut Transform)>) {
+    for (orbit, mut transform) in query.iter_mut() {
+        let angle = orbit.phase + time.elapsed_seconds() * orbit.speed;
+        transform.translation = Vec3::new(angle.cos(), 0.0, angle.sin()) * orbit.radius;
+    }
+}
+
+fn toggle_batching(keys: Res<ButtonInput<KeyCode>>, mut trail_query: Query<&mut Trail>) {
+    if !keys.just_pressed(KeyCode::KeyB) {
+        return;
+    }
+    let mut batched = false;
+    for mut trail in trail_query.iter_mut() {
+        trail.batched = !trail.batched;
+        batched = trail.batched;
+    }
+    let draw_calls = if batched { 1 } else { TRAIL_COUNT };
+    info!("Batching {batched}, {draw_calls} trail draw calls");
+}
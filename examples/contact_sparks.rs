@@ -0,0 +1,126 @@
+//! Event-driven trail: sparks are only laid where a bouncing ball touches the
+//! ground, by feeding contact events into `Trail::push_point` instead of
+//! emitting on a timer.
+
+use bevy::prelude::*;
+use bevy_trail::{EmitMode, Trail, TrailPlugin, TrailStyle};
+
+const BALL_RADIUS: f32 = 0.3;
+const GRAVITY: f32 = 9.81;
+
+/// Stand-in for the contact events a physics engine would send
+#[derive(Event)]
+struct ContactEvent {
+    point: Vec3,
+}
+
+#[derive(Component)]
+struct Ball {
+    velocity: Vec3,
+}
+
+#[derive(Component)]
+struct Sparks;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(TrailPlugin)
+        .add_event::<ContactEvent>()
+        .add_systems(Startup, setup)
+        .add_systems(Update, (move_ball, emit_sparks).chain())
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_xyz(0.0, 6.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_rotation(Quat::from_euler(EulerRot::XYZ, -0.8, -0.4, 0.0)),
+        ..default()
+    });
+
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(Plane3d::default().mesh().size(14.0, 14.0)),
+        material: materials.add(Color::srgb(0.2, 0.2, 0.25)),
+        ..default()
+    });
+
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(Sphere::new(BALL_RADIUS)),
+            material: materials.add(Color::WHITE),
+            transform: Transform::from_xyz(0.0, 3.0, 0.0),
+            ..default()
+        },
+        Ball {
+            velocity: Vec3::new(3.0, 0.0, 1.7),
+        },
+    ));
+
+    // The spark trail never emits on its own, it only records contacts
+    let spark_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(1.0, 0.7, 0.2),
+        unlit: true,
+        alpha_mode: AlphaMode::Blend,
+        ..default()
+    });
+    let mut trail = Trail::new(200, 1.0, 0.25, spark_material);
+    trail.emit_mode = EmitMode::Manual;
+    trail.style = TrailStyle::Quads { size_over_age: 0.8 };
+    commands.spawn((TransformBundle::default(), trail, Sparks));
+}
+
+fn move_ball(
+    time: Res<Time>,
+    mut ball_query: Query<(&mut Transform, &mut Ball)>,
+    mut contacts: EventWriter<ContactEvent>,
+) {
+    let dt = time.delta_seconds();
+
+    for (mut transform, mut ball) in ball_query.iter_mut() {
+        ball.velocity.y -= GRAVITY * dt;
+        transform.translation += ball.velocity * dt;
+
+        // Bounce off the edges of the ground
+        for axis in [0, 2] {
+            if transform.translation[axis].abs() > 6.0 {
+                ball.velocity[axis] = -ball.velocity[axis];
+            }
+        }
+
+        if transform.translation.y <= BALL_RADIUS {
+            transform.translation.y = BALL_RADIUS;
+            ball.velocity.y = ball.velocity.y.abs() * 0.7;
+            // Kick the ball back up once it has settled into a roll
+            if ball.velocity.y < 1.0 {
+                ball.velocity.y = 6.0;
+            }
+
+            contacts.send(ContactEvent {
+                point: transform.translation.with_y(0.01),
+            });
+        }
+    }
+}
+
+fn emit_sparks(
+    mut contacts: EventReader<ContactEvent>,
+    mut sparks_query: Query<&mut Trail, With<Sparks>>,
+) {
+    let Ok(mut trail) = sparks_query.get_single_mut() else {
+        return;
+    };
+
+    for contact in contacts.read() {
+        let timestamp = trail.clock();
+        trail.push_point(contact.point, timestamp);
+    }
+}
@@ -0,0 +1,61 @@
+//! Paint a ribbon on the ground by dragging with the left mouse button. The
+//! emitter follows the cursor through `CursorTrail` and only emits while the
+//! button is held.
+
+use bevy::prelude::*;
+use bevy_trail::{CursorTrail, EmitMode, Trail, TrailCommandsExt, TrailPlugin};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(TrailPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, toggle_painting)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_xyz(0.0, 10.0, 8.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_rotation(Quat::from_euler(EulerRot::XYZ, -1.0, -0.4, 0.0)),
+        ..default()
+    });
+
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(Plane3d::default().mesh().size(20.0, 20.0)),
+        material: materials.add(Color::srgb(0.2, 0.2, 0.25)),
+        ..default()
+    });
+
+    let mut trail = Trail::new(400, 60.0, 0.3, Handle::default());
+    trail.emit_mode = EmitMode::Manual;
+    // Lift the ribbon slightly so it doesn't z-fight with the ground
+    commands
+        .spawn_trail(Color::srgba(1.0, 0.6, 0.1, 0.9), trail)
+        .insert((
+            CursorTrail {
+                plane_origin: Vec3::Y * 0.01,
+                ..CursorTrail::ground()
+            },
+            TransformBundle::default(),
+        ));
+}
+
+fn toggle_painting(buttons: Res<ButtonInput<MouseButton>>, mut trail_query: Query<&mut Trail>) {
+    for mut trail in trail_query.iter_mut() {
+        // Back in manual mode the ribbon also lets go of the cursor
+        trail.emit_mode = if buttons.pressed(MouseButton::Left) {
+            EmitMode::Timer
+        } else {
+            EmitMode::Manual
+        };
+    }
+}
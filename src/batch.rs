@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+use bevy::render::mesh::{MeshVertexAttributeId, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::view::NoFrustumCulling;
+use bevy::utils::{Entry, HashMap};
+
+use crate::slot::MeshSlot;
+use crate::{Trail, Trail2d};
+
+/// Trails sharing a material and vertex layout, merged into one mesh
+type BatchKey = (AssetId<StandardMaterial>, Vec<MeshVertexAttributeId>);
+
+/// Mesh entities drawing the [`Trail::batched`] trails, one per material
+#[derive(Resource, Default)]
+pub(crate) struct TrailBatches {
+    batches: HashMap<BatchKey, MeshSlot>,
+    /// Trails and materials merged into the batches last time
+    members: Vec<(Entity, AssetId<StandardMaterial>)>,
+}
+
+/// Merge the meshes of batched trails into one mesh per material, in world
+/// space so trails recorded in different frames can share it
+pub(crate) fn generate_batched_trail_meshes(
+    mut commands: Commands,
+    mut batches: ResMut<TrailBatches>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut trail_query: Query<(Entity, &mut Trail), Without<Trail2d>>,
+    frame_query: Query<Ref<GlobalTransform>>,
+) {
+    // Only merge again once a batched trail was rebuilt, moved along with
+    // its reference frame, or joined or left a batch
+    let mut members = Vec::new();
+    let mut changed = false;
+    for (entity, mut trail) in trail_query.iter_mut() {
+        let has_mesh = trail
+            .mesh
+            .as_ref()
+            .is_some_and(|mesh| meshes.contains(mesh));
+        if !trail.batched || !has_mesh {
+            continue;
+        }
+        members.push((entity, trail.material.id()));
+        if trail.batch_dirty {
+            trail.batch_dirty = false;
+            changed = true;
+        }
+        changed |= trail
+            .frame_entity()
+            .and_then(|e| frame_query.get(e).ok())
+            .is_some_and(|frame| frame.is_changed());
+    }
+    if !changed && members == batches.members {
+        return;
+    }
+    batches.members = members;
+
+    let mut merged: HashMap<BatchKey, (Handle<StandardMaterial>, Mesh)> = HashMap::new();
+    for (_, trail) in trail_query.iter() {
+        if !trail.batched {
+            continue;
+        }
+        let Some(mesh) = trail.mesh.as_ref().and_then(|mesh| meshes.get(mesh)) else {
+            continue;
+        };
+        // Strip topologies can't be merged without bridging triangles
+        if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+            continue;
+        }
+
+        let mut mesh = mesh.clone();
+        mesh.asset_usage = RenderAssetUsages::default();
+        if let Some(frame) = trail.frame_entity().and_then(|e| frame_query.get(e).ok()) {
+            mesh.transform_by(frame.compute_transform());
+        }
+        let mut attributes: Vec<_> = mesh.attributes().map(|(id, _)| id).collect();
+        attributes.sort();
+        match merged.entry((trail.material.id(), attributes)) {
+            Entry::Occupied(mut entry) => entry.get_mut().1.merge(&mesh),
+            Entry::Vacant(entry) => {
+                entry.insert((trail.material.clone(), mesh));
+            }
+        }
+    }
+
+    // Drop batches whose trails have all gone
+    batches.batches.retain(|key, slot| {
+        let keep = merged.contains_key(key);
+        if !keep {
+            slot.clear(&mut commands, &mut meshes);
+        }
+        keep
+    });
+
+    for (key, (material, mesh)) in merged {
+        let slot = batches.batches.entry(key).or_default();
+        // The batch spans all its trails, so never cull it by a stale bound
+        slot.show(&mut commands, &mut meshes, mesh, |handle| {
+            (
+                PbrBundle {
+                    mesh: handle,
+                    material,
+                    ..default()
+                },
+                NoFrustumCulling,
+            )
+        });
+    }
+}
@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+
+use crate::{
+    CapStyle, EmitMode, OrientMode, TaperMode, Trail, TrailBreathing, TrailConfigAsset,
+    TrailProfile, TrailSpace, TrailStyle, WidthCurve,
+};
+
+/// Default `max_points` of [`TrailBuilder`]
+const DEFAULT_MAX_POINTS: usize = 50;
+/// Default `emit_rate` of [`TrailBuilder`]
+const DEFAULT_EMIT_RATE: f32 = 20.0;
+/// Default `width` of [`TrailBuilder`]
+const DEFAULT_WIDTH: f32 = 0.5;
+
+/// Generates a chainable setter for each plain [`Trail`] field
+macro_rules! setters {
+    ($($(#[$doc:meta])* $field:ident: $ty:ty,)*) => {
+        $(
+            $(#[$doc])*
+            pub fn $field(mut self, $field: $ty) -> Self {
+                self.trail.$field = $field;
+                self
+            }
+        )*
+    };
+}
+
+/// Chainable construction of a [`Trail`], starting from sensible defaults.
+///
+/// Each method sets the [`Trail`] field of the same name.
+pub struct TrailBuilder {
+    trail: Trail,
+    jitter: Option<(f32, u64)>,
+    initial_points: Vec<Vec3>,
+}
+
+impl Default for TrailBuilder {
+    fn default() -> Self {
+        Self {
+            trail: Trail::new(
+                DEFAULT_MAX_POINTS,
+                DEFAULT_EMIT_RATE,
+                DEFAULT_WIDTH,
+                Handle::default(),
+            ),
+            jitter: None,
+            initial_points: Vec::new(),
+        }
+    }
+}
+
+impl TrailBuilder {
+    /// Points emitted per second, also resizing the emission timer. Rates
+    /// that aren't positive and finite are ignored with a warning.
+    pub fn emit_rate(mut self, emit_rate: f32) -> Self {
+        self.trail.set_emit_rate(emit_rate);
+        self
+    }
+
+    /// Offset the timer phase, see [`Trail::with_timer_jitter`]
+    pub fn timer_jitter(mut self, jitter: f32, seed: u64) -> Self {
+        self.jitter = Some((jitter, seed));
+        self
+    }
+
+    /// Start the trail out along a complete path, see [`Trail::push_path`]
+    pub fn initial_points(mut self, points: impl IntoIterator<Item = Vec3>) -> Self {
+        self.initial_points = points.into_iter().collect();
+        self
+    }
+
+    /// Width keyframes, see [`Trail::set_width_keyframes`]
+    pub fn width_keyframes(mut self, keyframes: impl Into<Vec<(f32, f32)>>) -> Self {
+        self.trail.set_width_keyframes(keyframes);
+        self
+    }
+
+    setters! {
+        max_points: usize,
+        max_length: Option<f32>,
+        emit_mode: EmitMode,
+        emit_position_blend: f32,
+        position_smoothing: f32,
+        prefill_on_spawn: Option<usize>,
+        width: f32,
+        left_width: Option<f32>,
+        right_width: Option<f32>,
+        taper: TaperMode,
+        width_curve: Option<WidthCurve>,
+        cap: CapStyle,
+        up_axis: Vec3,
+        fallback_axis: Vec3,
+        orient: OrientMode,
+        twist: f32,
+        style: TrailStyle,
+        tessellation: (usize, usize),
+        uv_tiling: f32,
+        uv_scroll_speed: f32,
+        clamp_to_curvature: bool,
+        face_camera: bool,
+        max_age: Option<f32>,
+        max_age_frames: Option<u64>,
+        breathing: Option<TrailBreathing>,
+        render: bool,
+        paused: bool,
+        mesh_update_hz: Option<f32>,
+        max_stale_frames: Option<u32>,
+        priority: i32,
+        edge_attribute: bool,
+        triangle_strip: bool,
+        batched: bool,
+        frustum_culling: bool,
+        fade_out: Option<f32>,
+        simplify_tolerance: Option<f32>,
+        smoothing: usize,
+        fade_duration: Option<f32>,
+        fade_with_age: bool,
+        color_gradient: Option<(Color, Color)>,
+        merge_epsilon: f32,
+        min_movement: f32,
+        attach_head: bool,
+        material: Handle<StandardMaterial>,
+        double_sided: bool,
+        config: Option<Handle<TrailConfigAsset>>,
+        render_layers: Option<RenderLayers>,
+        reference_frame: Option<Entity>,
+        space: TrailSpace,
+        inherit_velocity: bool,
+        profile: Option<Handle<TrailProfile>>,
+    }
+
+    pub fn build(self) -> Trail {
+        let mut trail = match self.jitter {
+            Some((jitter, seed)) => self.trail.with_timer_jitter(jitter, seed),
+            None => self.trail,
+        };
+        trail.push_path(self.initial_points);
+        trail
+    }
+}
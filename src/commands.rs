@@ -0,0 +1,39 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+
+use crate::Trail;
+
+/// Spawn trails without creating and registering a material by hand
+pub trait TrailCommandsExt {
+    /// Spawn an entity carrying `trail`, replacing its material with a new
+    /// translucent [`StandardMaterial`] of `color`. Insert the emitter's
+    /// transform and mesh on the returned [`EntityCommands`].
+    fn spawn_trail(&mut self, color: Color, trail: Trail) -> EntityCommands<'_>;
+}
+
+impl TrailCommandsExt for Commands<'_, '_> {
+    fn spawn_trail(&mut self, color: Color, mut trail: Trail) -> EntityCommands<'_> {
+        let entity = self.spawn_empty().id();
+        self.add(move |world: &mut World| {
+            trail.material = world
+                .resource_mut::<Assets<StandardMaterial>>()
+                .add(trail_material(color));
+            if let Some(mut entity) = world.get_entity_mut(entity) {
+                entity.insert(trail);
+            }
+        });
+        self.entity(entity)
+    }
+}
+
+/// The material [`TrailCommandsExt::spawn_trail`] creates for a color,
+/// double-sided so the ribbon shows from both sides
+pub fn trail_material(color: Color) -> StandardMaterial {
+    StandardMaterial {
+        base_color: color,
+        alpha_mode: AlphaMode::Blend,
+        double_sided: true,
+        cull_mode: None,
+        ..default()
+    }
+}
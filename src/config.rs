@@ -0,0 +1,139 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::Trail;
+
+/// Trail parameters authored in a `.trail.ron` file and shared between
+/// trails through [`Trail::config`].
+///
+/// Every field is optional so a config can override only some settings.
+/// Trails pick up changes whenever the asset is loaded or hot-reloaded.
+#[derive(Asset, TypePath, Clone, Debug, Default, Deserialize, Serialize)]
+pub struct TrailConfigAsset {
+    #[serde(default)]
+    pub emit_rate: Option<f32>,
+    #[serde(default)]
+    pub width: Option<f32>,
+    #[serde(default)]
+    pub max_points: Option<usize>,
+    /// sRGB color with alpha, applied as the base color of the trail material.
+    /// Ignored with a warning while a [`TrailMaterial`] draws the trail.
+    ///
+    /// [`TrailMaterial`]: crate::TrailMaterial
+    #[serde(default)]
+    pub color: Option<[f32; 4]>,
+}
+
+impl TrailConfigAsset {
+    /// Copy the configured values onto `trail` and its material
+    pub fn apply(&self, trail: &mut Trail, materials: &mut Assets<StandardMaterial>) {
+        if let Some(emit_rate) = self.emit_rate {
+            trail.set_emit_rate(emit_rate);
+        }
+        if let Some(width) = self.width {
+            trail.set_width(width);
+        }
+        if let Some(max_points) = self.max_points {
+            trail.max_points = max_points;
+        }
+        if let (Some(_), true) = (self.color, trail.custom_material) {
+            warn!("Trail config color has no effect while a TrailMaterial draws the trail");
+        } else if let Some([r, g, b, a]) = self.color {
+            if let Some(material) = materials.get_mut(&trail.material) {
+                material.base_color = Color::srgba(r, g, b, a);
+            }
+        }
+    }
+}
+
+/// Loads [`TrailConfigAsset`]s from RON
+#[derive(Default)]
+pub struct TrailConfigLoader;
+
+impl AssetLoader for TrailConfigLoader {
+    type Asset = TrailConfigAsset;
+    type Settings = ();
+    type Error = RonAssetError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["trail.ron"]
+    }
+}
+
+/// Error loading one of the crate's RON assets
+#[derive(Debug)]
+pub enum RonAssetError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl fmt::Display for RonAssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "could not read asset: {error}"),
+            Self::Ron(error) => write!(f, "could not parse RON: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for RonAssetError {}
+
+impl From<std::io::Error> for RonAssetError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for RonAssetError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        Self::Ron(error)
+    }
+}
+
+pub(crate) fn apply_trail_configs(
+    mut events: EventReader<AssetEvent<TrailConfigAsset>>,
+    configs: Res<Assets<TrailConfigAsset>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut trail_query: Query<&mut Trail>,
+) {
+    let changed: HashSet<AssetId<TrailConfigAsset>> = events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Added { id }
+            | AssetEvent::Modified { id }
+            | AssetEvent::LoadedWithDependencies { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    for mut trail in trail_query.iter_mut() {
+        let Some(handle) = &trail.config else {
+            continue;
+        };
+        let id = handle.id();
+        if trail.applied_config == Some(id) && !changed.contains(&id) {
+            continue;
+        }
+        let Some(config) = configs.get(handle) else {
+            continue;
+        };
+
+        config.apply(&mut trail, &mut materials);
+        trail.applied_config = Some(id);
+    }
+}
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+/// Moves its entity to where the cursor meets a plane in world space, so a
+/// [`crate::Trail`] on the same entity follows the pointer.
+#[derive(Component, Clone, Debug)]
+pub struct CursorTrail {
+    /// Any point on the plane the cursor is projected onto
+    pub plane_origin: Vec3,
+    /// Normal of the projection plane
+    pub plane_normal: Dir3,
+    /// Camera to cast from, the first active 3d camera when `None`
+    pub camera: Option<Entity>,
+}
+
+impl Default for CursorTrail {
+    fn default() -> Self {
+        Self::ground()
+    }
+}
+
+impl CursorTrail {
+    /// Follow the cursor across the XZ ground plane
+    pub fn ground() -> Self {
+        Self {
+            plane_origin: Vec3::ZERO,
+            plane_normal: Dir3::Y,
+            camera: None,
+        }
+    }
+}
+
+pub(crate) fn follow_cursor(
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(Entity, &Camera, &GlobalTransform), With<Camera3d>>,
+    mut cursor_query: Query<(&CursorTrail, &mut Transform)>,
+) {
+    let Some(cursor) = window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    else {
+        return;
+    };
+
+    for (cursor_trail, mut transform) in cursor_query.iter_mut() {
+        let camera = match cursor_trail.camera {
+            Some(entity) => camera_query.get(entity).ok(),
+            None => camera_query.iter().find(|(_, camera, _)| camera.is_active),
+        };
+        let Some((_, camera, camera_transform)) = camera else {
+            continue;
+        };
+        let Some(ray) = camera.viewport_to_world(camera_transform, cursor) else {
+            continue;
+        };
+        let plane = InfinitePlane3d::new(cursor_trail.plane_normal);
+        if let Some(distance) = ray.intersect_plane(cursor_trail.plane_origin, plane) {
+            transform.translation = ray.get_point(distance);
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use bevy::color::palettes::css::{AQUA, WHITE, YELLOW};
+use bevy::prelude::*;
+
+use crate::{ribbon_frames, Trail, TrailProfile};
+
+/// Draws every trail's stored points, centerline and ribbon edges with
+/// `Gizmos` while `enabled` is set, for tuning emission and width. Nothing
+/// runs while it is disabled.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct TrailDebug {
+    /// Whether to draw the debug overlay
+    pub enabled: bool,
+    /// Radius of the sphere drawn at each stored point
+    pub point_radius: f32,
+}
+
+impl Default for TrailDebug {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            point_radius: 0.05,
+        }
+    }
+}
+
+pub(crate) fn trail_debug_enabled(debug: Res<TrailDebug>) -> bool {
+    debug.enabled
+}
+
+pub(crate) fn draw_trail_debug(
+    debug: Res<TrailDebug>,
+    profiles: Res<Assets<TrailProfile>>,
+    mut gizmos: Gizmos,
+    trail_query: Query<&Trail>,
+    camera_query: Query<&GlobalTransform, With<Camera3d>>,
+    frame_query: Query<&GlobalTransform>,
+) {
+    let view = camera_query.iter().next();
+
+    for trail in trail_query.iter() {
+        // Points live in the reference frame's space when one is set
+        let frame = trail
+            .frame_entity()
+            .and_then(|e| frame_query.get(e).ok())
+            .map(GlobalTransform::affine);
+        let to_world = |v: Vec3| frame.map_or(v, |frame| frame.transform_point3(v));
+
+        for point in trail.points.iter() {
+            gizmos.sphere(
+                to_world(point.position),
+                Quat::IDENTITY,
+                debug.point_radius,
+                YELLOW,
+            );
+        }
+
+        let points = trail.render_points();
+        if points.len() < 2 {
+            continue;
+        }
+        gizmos.linestrip(points.iter().map(|point| to_world(point.position)), WHITE);
+
+        // The same edges `create_trail_mesh` places its outer vertices on
+        let profile = trail
+            .profile
+            .as_ref()
+            .and_then(|profile| profiles.get(profile));
+        let camera = view.map(|view| {
+            let camera = view.translation();
+            frame.map_or(camera, |frame| frame.inverse().transform_point3(camera))
+        });
+        let frames = ribbon_frames(&points, trail, profile, camera);
+        gizmos.linestrip(
+            frames
+                .iter()
+                .map(|frame| to_world(frame.position - frame.right * frame.left_width)),
+            AQUA,
+        );
+        gizmos.linestrip(
+            frames
+                .iter()
+                .map(|frame| to_world(frame.position + frame.right * frame.right_width)),
+            AQUA,
+        );
+    }
+}
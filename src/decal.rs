@@ -0,0 +1,188 @@
+use bevy::color::ColorToPacked;
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::Trail;
+
+/// Paints a trail's points into an image mapped onto a flat surface, so the
+/// marks persist after the trail itself has expired (tire marks, scorch
+/// lines). Insert next to a [`Trail`] and use `image` as the surface's texture.
+#[derive(Component, Clone, Debug)]
+pub struct TrailDecal {
+    /// Image painted into, must be `Rgba8UnormSrgb` or `Rgba8Unorm`
+    pub image: Handle<Image>,
+    /// Mapping from world space onto the image
+    pub projection: DecalProjection,
+    /// Brush radius in world units
+    pub radius: f32,
+    /// Paint color, alpha blended over the existing contents
+    pub color: Color,
+    /// Last painted world position and its point timestamp
+    pub(crate) last_painted: Option<(Vec3, f32)>,
+}
+
+impl TrailDecal {
+    pub fn new(
+        image: Handle<Image>,
+        projection: DecalProjection,
+        radius: f32,
+        color: Color,
+    ) -> Self {
+        Self {
+            image,
+            projection,
+            radius,
+            color,
+            last_painted: None,
+        }
+    }
+
+    /// Create a transparent image to paint decals into
+    pub fn blank_image(width: u32, height: u32) -> Image {
+        Image::new_fill(
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0, 0, 0, 0],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        )
+    }
+}
+
+/// Planar projection from world space into a decal image's UV space
+#[derive(Clone, Copy, Debug)]
+pub struct DecalProjection {
+    /// World position of the image's center
+    pub origin: Vec3,
+    /// World direction of the image's U axis
+    pub u_axis: Vec3,
+    /// World direction of the image's V axis
+    pub v_axis: Vec3,
+    /// World-space extent covered by the image
+    pub size: Vec2,
+}
+
+impl DecalProjection {
+    /// Projection onto the XZ ground plane, centered on `origin`
+    pub fn ground(origin: Vec3, size: Vec2) -> Self {
+        Self {
+            origin,
+            u_axis: Vec3::X,
+            v_axis: Vec3::Z,
+            size,
+        }
+    }
+
+    /// Map a world position to UV coordinates, `0..1` inside the image
+    pub fn project(&self, position: Vec3) -> Vec2 {
+        let local = position - self.origin;
+        Vec2::new(
+            local.dot(self.u_axis) / self.size.x + 0.5,
+            local.dot(self.v_axis) / self.size.y + 0.5,
+        )
+    }
+}
+
+pub(crate) fn paint_trail_decals(
+    mut images: ResMut<Assets<Image>>,
+    mut decal_query: Query<(&Trail, &mut TrailDecal)>,
+    frame_query: Query<&GlobalTransform>,
+    mut warned_format: Local<bool>,
+) {
+    for (trail, mut decal) in decal_query.iter_mut() {
+        // Start a new stroke once the trail empties, instead of bridging the
+        // gap to wherever it begins again
+        let Some(newest) = trail.points.back() else {
+            decal.last_painted = None;
+            continue;
+        };
+        if decal
+            .last_painted
+            .is_some_and(|(_, timestamp)| timestamp == newest.timestamp)
+        {
+            continue;
+        }
+
+        let Some(image) = images.get_mut(&decal.image) else {
+            continue;
+        };
+        let color = match image.texture_descriptor.format {
+            TextureFormat::Rgba8UnormSrgb => decal.color.to_srgba().to_u8_array(),
+            TextureFormat::Rgba8Unorm => decal.color.to_linear().to_u8_array(),
+            format => {
+                if !*warned_format {
+                    warn!("TrailDecal images must be Rgba8, got {:?}", format);
+                    *warned_format = true;
+                }
+                continue;
+            }
+        };
+
+        // Points live in the reference frame's space when one is set
+        let to = trail
+            .frame_entity()
+            .and_then(|e| frame_query.get(e).ok())
+            .map_or(newest.position, |frame| {
+                frame.transform_point(newest.position)
+            });
+
+        // Stamp the brush along the segment since the last painted point so
+        // fast movers leave a continuous mark
+        let from = decal.last_painted.map_or(to, |(p, _)| p);
+        let spacing = (decal.radius * 0.5).max(f32::EPSILON);
+        let steps = (from.distance(to) / spacing).ceil().max(1.0) as usize;
+        for step in 1..=steps {
+            let position = from.lerp(to, step as f32 / steps as f32);
+            stamp(image, &decal.projection, position, decal.radius, color);
+        }
+
+        decal.last_painted = Some((to, newest.timestamp));
+    }
+}
+
+/// Alpha blend a filled circle of world `radius` into the image
+fn stamp(
+    image: &mut Image,
+    projection: &DecalProjection,
+    position: Vec3,
+    radius: f32,
+    color: [u8; 4],
+) {
+    let size = Vec2::new(image.width() as f32, image.height() as f32);
+    let center = projection.project(position) * size;
+    let radius_px = radius / projection.size * size;
+    if !radius_px.cmpgt(Vec2::ZERO).all() {
+        return;
+    }
+
+    let min = (center - radius_px).floor().max(Vec2::ZERO);
+    let max = (center + radius_px).ceil().min(size - 1.0);
+    if min.x > max.x || min.y > max.y {
+        return;
+    }
+
+    let alpha = color[3] as f32 / 255.0;
+    let width = image.width() as usize;
+    for y in min.y as usize..=max.y as usize {
+        for x in min.x as usize..=max.x as usize {
+            let offset = (Vec2::new(x as f32, y as f32) + 0.5 - center) / radius_px;
+            if offset.length_squared() > 1.0 {
+                continue;
+            }
+
+            let i = (y * width + x) * 4;
+            let pixel = &mut image.data[i..i + 4];
+            for channel in 0..3 {
+                pixel[channel] = (color[channel] as f32 * alpha
+                    + pixel[channel] as f32 * (1.0 - alpha))
+                    .round() as u8;
+            }
+            pixel[3] = pixel[3].max(color[3]);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+use bevy::render::mesh::{PrimitiveTopology, VertexAttributeValues};
+use std::fmt::Write;
+
+/// Convert a triangle mesh into a Wavefront OBJ string.
+///
+/// Positions, normals and UVs are written when present. UVs are flipped
+/// vertically since OBJ puts the texture origin at the bottom left. Triangle
+/// strips are split into individual faces with a consistent winding.
+pub fn mesh_to_obj(mesh: &Mesh) -> String {
+    let mut obj = String::new();
+
+    let positions = match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+        Some(VertexAttributeValues::Float32x3(positions)) => positions,
+        _ => return obj,
+    };
+    let normals = match mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+        Some(VertexAttributeValues::Float32x3(normals)) => Some(normals),
+        _ => None,
+    };
+    let uvs = match mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+        Some(VertexAttributeValues::Float32x2(uvs)) => Some(uvs),
+        _ => None,
+    };
+
+    for [x, y, z] in positions {
+        let _ = writeln!(obj, "v {x} {y} {z}");
+    }
+    for [u, v] in uvs.into_iter().flatten() {
+        let _ = writeln!(obj, "vt {u} {}", 1.0 - v);
+    }
+    for [x, y, z] in normals.into_iter().flatten() {
+        let _ = writeln!(obj, "vn {x} {y} {z}");
+    }
+
+    let indices: Vec<usize> = match mesh.indices() {
+        Some(indices) => indices.iter().collect(),
+        None => (0..positions.len()).collect(),
+    };
+    let triangles: Vec<[usize; 3]> = match mesh.primitive_topology() {
+        PrimitiveTopology::TriangleList => indices
+            .chunks_exact(3)
+            .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+            .collect(),
+        // Every other strip triangle is wound the opposite way
+        PrimitiveTopology::TriangleStrip => indices
+            .windows(3)
+            .enumerate()
+            .map(|(i, triangle)| match i % 2 {
+                0 => [triangle[0], triangle[1], triangle[2]],
+                _ => [triangle[1], triangle[0], triangle[2]],
+            })
+            .collect(),
+        _ => return obj,
+    };
+
+    for triangle in triangles {
+        obj.push('f');
+        for index in triangle {
+            // OBJ indices are 1-based
+            let i = index + 1;
+            let _ = match (uvs.is_some(), normals.is_some()) {
+                (true, true) => write!(obj, " {i}/{i}/{i}"),
+                (true, false) => write!(obj, " {i}/{i}"),
+                (false, true) => write!(obj, " {i}//{i}"),
+                (false, false) => write!(obj, " {i}"),
+            };
+        }
+        obj.push('\n');
+    }
+
+    obj
+}
@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+use bevy::render::mesh::VertexAttributeValues;
+
+/// Fades out a mesh entity a [`Trail`](crate::Trail) with `fade_out` left
+/// behind when it emptied or was removed, then despawns it along with its
+/// mesh.
+///
+/// The fade scales the mesh's vertex alpha, so it needs a material with an
+/// alpha blending mode.
+#[derive(Component, Clone, Debug)]
+pub struct TrailFadeOut {
+    duration: f32,
+    elapsed: f32,
+    /// Vertex colors at the start of the fade
+    colors: Option<Vec<[f32; 4]>>,
+}
+
+impl TrailFadeOut {
+    /// Fade out over `duration` seconds. Insert it on any entity with a
+    /// `Handle<Mesh>`, such as a detached trail mesh, to fade and despawn it.
+    pub fn new(duration: f32) -> Self {
+        Self {
+            duration,
+            elapsed: 0.0,
+            colors: None,
+        }
+    }
+
+    /// Fraction of the fade completed, from 0 to 1
+    pub fn progress(&self) -> f32 {
+        if self.duration > 0.0 {
+            (self.elapsed / self.duration).clamp(0.0, 1.0)
+        } else {
+            1.0
+        }
+    }
+}
+
+pub(crate) fn fade_out_trail_meshes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut fade_query: Query<(Entity, &mut TrailFadeOut, &Handle<Mesh>)>,
+) {
+    for (entity, mut fade, handle) in fade_query.iter_mut() {
+        fade.elapsed += time.delta_seconds();
+        if fade.progress() >= 1.0 {
+            commands.entity(entity).despawn_recursive();
+            meshes.remove(handle);
+            continue;
+        }
+
+        let Some(mesh) = meshes.get_mut(handle) else {
+            continue;
+        };
+        let alpha = 1.0 - fade.progress();
+        // Remember the starting colors, opaque white for uncolored meshes
+        if fade.colors.is_none() {
+            fade.colors = Some(match mesh.attribute(Mesh::ATTRIBUTE_COLOR) {
+                Some(VertexAttributeValues::Float32x4(colors)) => colors.clone(),
+                _ => vec![[1.0; 4]; mesh.count_vertices()],
+            });
+        }
+        let faded: Vec<[f32; 4]> = fade
+            .colors
+            .iter()
+            .flatten()
+            .map(|&[r, g, b, a]| [r, g, b, a * alpha])
+            .collect();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, faded);
+    }
+}
@@ -0,0 +1,3410 @@
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, MeshVertexAttribute, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{
+    Extent3d, TextureDimension, TextureFormat, TextureUsages, VertexFormat,
+};
+use bevy::render::primitives::Aabb;
+use bevy::render::view::{NoFrustumCulling, RenderLayers};
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+mod batch;
+mod builder;
+mod commands;
+mod config;
+mod cursor;
+mod debug;
+mod decal;
+mod export;
+mod fade;
+mod material;
+mod particles;
+mod profile;
+mod rider;
+mod slot;
+mod spatial;
+mod trail2d;
+mod ui;
+
+pub use builder::TrailBuilder;
+pub use commands::{trail_material, TrailCommandsExt};
+pub use config::{RonAssetError, TrailConfigAsset, TrailConfigLoader};
+pub use cursor::CursorTrail;
+pub use debug::TrailDebug;
+pub use decal::{DecalProjection, TrailDecal};
+pub use export::mesh_to_obj;
+pub use fade::TrailFadeOut;
+pub use material::{
+    SoftDepthMaterial, SoftDepthMaterialPlugin, SoftEdgeMaterial, SoftEdgeMaterialPlugin,
+    TrailMaterial, TrailMaterialPlugin,
+};
+pub use particles::ParticleTrails;
+pub use profile::{TrailProfile, TrailProfileLoader};
+pub use rider::TrailRider;
+pub use spatial::{IndexedTrail, TrailSpatialIndex};
+pub use trail2d::Trail2d;
+pub use ui::UiTrail;
+
+/// Ribbon edge coordinate written when [`Trail::edge_attribute`] is set.
+///
+/// `x` runs from -1 on the left edge through 0 on the centerline to 1 on the
+/// right edge, and `y` holds the local half-width in world units, so shaders
+/// can compute the distance to the nearest edge and feather the alpha.
+pub const ATTRIBUTE_EDGE_DISTANCE: MeshVertexAttribute =
+    MeshVertexAttribute::new("TrailEdgeDistance", 988_540_917, VertexFormat::Float32x2);
+
+pub struct TrailPlugin;
+
+/// Stages of the trail systems in `Update`, in the order they run. Every
+/// system of the plugin is in one of them. Order your movement
+/// `.before(TrailSystemSet::Sample)` so trails sample this frame's transforms,
+/// and camera updates for billboarded trails
+/// `.before(TrailSystemSet::GenerateMesh)`.
+///
+/// The stages stay in `Update` rather than running in `PostUpdate` after
+/// transform propagation. Trails sample the emitter's `Transform`, which is
+/// already current once movement runs before `Sample`, and the mesh entities
+/// they spawn and move still get their `GlobalTransform` propagated later in
+/// the same frame. Only [`Trail::inherit_velocity`] samples the
+/// `GlobalTransform`, which it extrapolates to make up for it being a frame
+/// old.
+#[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TrailSystemSet {
+    /// Budgets, configs and profiles are applied, then emitters are sampled
+    /// and points are recorded and expired
+    Sample,
+    /// Meshes are rebuilt from the points and their entities are updated,
+    /// along with everything else reading the new points such as decals, the
+    /// spatial index, riders and debug drawing
+    GenerateMesh,
+}
+
+impl Plugin for TrailPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Trail>()
+            .observe(despawn_removed_trail_meshes)
+            .observe(trail2d::despawn_removed_trail_2d_meshes)
+            .observe(particles::despawn_removed_particle_trail_meshes)
+            .observe(ui::despawn_removed_ui_trail_meshes)
+            .init_resource::<BeatClock>()
+            .init_resource::<TrailRebuildBudget>()
+            .init_resource::<TrailBudget>()
+            .init_resource::<TrailSpatialIndex>()
+            .init_resource::<TrailDebug>()
+            .init_resource::<batch::TrailBatches>()
+            .add_event::<TrailPointEmitted>()
+            .configure_sets(
+                Update,
+                (TrailSystemSet::Sample, TrailSystemSet::GenerateMesh).chain(),
+            )
+            .add_systems(
+                Update,
+                apply_trail_budget
+                    .before(update_trails)
+                    .in_set(TrailSystemSet::Sample),
+            )
+            .init_asset::<TrailConfigAsset>()
+            .init_asset_loader::<TrailConfigLoader>()
+            .init_asset::<TrailProfile>()
+            .init_asset_loader::<TrailProfileLoader>()
+            .add_systems(
+                Update,
+                (
+                    config::apply_trail_configs,
+                    cursor::follow_cursor,
+                    profile::refresh_profiled_trails,
+                )
+                    .before(update_trails)
+                    .in_set(TrailSystemSet::Sample),
+            )
+            .add_systems(Update, update_trails.in_set(TrailSystemSet::Sample))
+            .add_systems(
+                Update,
+                (generate_trail_meshes, sync_trail_mesh_entities, update_trail_mesh_bounds)
+                    .chain()
+                    .in_set(TrailSystemSet::GenerateMesh),
+            )
+            .add_systems(
+                Update,
+                batch::generate_batched_trail_meshes
+                    .after(generate_trail_meshes)
+                    .in_set(TrailSystemSet::GenerateMesh),
+            )
+            .add_systems(
+                Update,
+                apply_double_sided_materials
+                    .before(sync_trail_mesh_entities)
+                    .in_set(TrailSystemSet::GenerateMesh),
+            )
+            .add_systems(
+                Update,
+                trail2d::generate_trail_meshes_2d.in_set(TrailSystemSet::GenerateMesh),
+            )
+            .add_systems(
+                Update,
+                (
+                    decal::paint_trail_decals,
+                    spatial::update_trail_spatial_index,
+                    rider::update_trail_riders,
+                    fade::fade_out_trail_meshes.after(sync_trail_mesh_entities),
+                    debug::draw_trail_debug.run_if(debug::trail_debug_enabled),
+                )
+                    .in_set(TrailSystemSet::GenerateMesh),
+            )
+            .add_systems(
+                Update,
+                (
+                    particles::update_particle_trails.in_set(TrailSystemSet::Sample),
+                    particles::generate_particle_trail_meshes.in_set(TrailSystemSet::GenerateMesh),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    ui::update_ui_trails.in_set(TrailSystemSet::Sample),
+                    ui::generate_ui_trail_meshes.in_set(TrailSystemSet::GenerateMesh),
+                ),
+            );
+    }
+}
+
+/// How a trail decides when to record a new point
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect)]
+pub enum EmitMode {
+    /// Emit on the trail's own timer, `emit_rate` times per second
+    #[default]
+    Timer,
+    /// Emit whenever the shared [`BeatClock`] pulses
+    Beat,
+    /// Never emit automatically, points only come from [`Trail::push_point`]
+    Manual,
+    /// Emit when the direction of travel since the newest point turns by more
+    /// than `angle_threshold` radians, or once the emitter is `max_distance`
+    /// away from it. Keeps long paths compact while preserving their shape.
+    OnTurn { angle_threshold: f32, max_distance: f32 },
+    /// Emit once the emitter has moved at least this far from the newest
+    /// point, for an even spacing whatever the speed. The first point is
+    /// always emitted.
+    Distance(f32),
+}
+
+/// Shared pulse source for trails using [`EmitMode::Beat`].
+///
+/// Call [`BeatClock::pulse`] from your own music system on every beat and all
+/// beat-driven trails record a point on the next update.
+#[derive(Resource, Default)]
+pub struct BeatClock {
+    beats: u64,
+}
+
+impl BeatClock {
+    /// Signal a beat
+    pub fn pulse(&mut self) {
+        self.beats += 1;
+    }
+    
+    /// Total number of beats signalled so far
+    pub fn beats(&self) -> u64 {
+        self.beats
+    }
+}
+
+/// Limits how many trail meshes are rebuilt each frame.
+///
+/// Trails over the limit keep their previous mesh until a later frame, with
+/// higher [`Trail::priority`] trails rebuilt first.
+#[derive(Resource, Default, Clone, Copy, Debug)]
+pub struct TrailRebuildBudget {
+    /// Maximum rebuilds per frame, `None` for no limit
+    pub max_rebuilds_per_frame: Option<usize>,
+}
+
+/// Limits how many trails emit at once in crowded scenes.
+///
+/// Trails beyond the limit stop emitting and let their existing points expire,
+/// or retract from the tail at their emission rate when they have neither
+/// `max_age` nor `max_age_frames`. Higher [`Trail::priority`] trails stay
+/// active first, then the ones nearest the camera.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct TrailBudget {
+    /// Maximum number of emitting trails
+    pub max_active: usize,
+}
+
+impl Default for TrailBudget {
+    fn default() -> Self {
+        Self {
+            max_active: usize::MAX,
+        }
+    }
+}
+
+/// Sent by `update_trails` whenever a trail emits a new point
+#[derive(Event, Clone, Copy, Debug)]
+pub struct TrailPointEmitted {
+    /// Entity of the emitting [`Trail`]
+    pub entity: Entity,
+    /// Position of the point, in the space the trail records its points in
+    pub position: Vec3,
+    /// Trail clock time the point was emitted at
+    pub timestamp: f32,
+}
+
+/// How the trail's points are turned into geometry
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect)]
+pub enum TrailStyle {
+    /// A continuous ribbon connecting consecutive points
+    #[default]
+    Ribbon,
+    /// An independent camera-facing quad per point that shrinks and fades
+    /// with age, losing `size_over_age` of its size every second
+    Quads { size_over_age: f32 },
+    /// Overlapping camera-facing puffs per point that grow by
+    /// `radius_over_age` of their size every second and fade out over the
+    /// point's `max_age`. Pair with a material whose `base_color_texture` is
+    /// [`soft_puff_image`] so neighbouring puffs blend into soft smoke.
+    SoftPuffs { radius_over_age: f32 },
+}
+
+/// Space a trail's points are recorded in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum TrailSpace {
+    /// World space, or the `reference_frame`'s space when one is set
+    #[default]
+    World,
+    /// Relative to the emitter's parent, so the trail history and its mesh move
+    /// with the parent, e.g. on a turret or in a cockpit. Overrides
+    /// `reference_frame`.
+    Local,
+}
+
+/// How the ribbon's width tapers along its length
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum TaperMode {
+    /// Constant width
+    None,
+    /// Zero width at the tail, full width at the head
+    #[default]
+    TailToHead,
+    /// Full width at the tail, zero width at the head
+    HeadToTail,
+    /// Zero width at both ends, full width in the middle
+    Both,
+}
+
+impl TaperMode {
+    /// Width factor at `progress`, 0 at the tail and 1 at the head
+    fn factor(self, progress: f32) -> f32 {
+        match self {
+            Self::None => 1.0,
+            Self::TailToHead => progress,
+            Self::HeadToTail => 1.0 - progress,
+            Self::Both => 1.0 - (progress * 2.0 - 1.0).abs(),
+        }
+    }
+}
+
+/// How the ribbon's cross-section is oriented along the trail
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect)]
+pub enum OrientMode {
+    /// Derive the right vector from the direction of travel and `up_axis`,
+    /// or face the camera with `face_camera`
+    #[default]
+    Adaptive,
+    /// Lock the ribbon's normal to this axis so it always lies flat in the
+    /// plane perpendicular to it, e.g. `Vec3::Y` for skid marks. Overrides
+    /// `face_camera`.
+    FixedAxis(Vec3),
+    /// Start from the adaptive orientation of the first segment and carry
+    /// each right vector on to the next point as it's recorded, keeping it
+    /// perpendicular to the direction of travel. The ribbon then turns
+    /// smoothly through vertical segments instead of rolling around
+    /// `fallback_axis`, at the cost of its roll drifting along winding paths.
+    /// Points keep their roll as the tail expires. Overrides `face_camera`.
+    ParallelTransport,
+}
+
+/// Width multiplier along the ribbon, mapping progress from the tail at 0 to
+/// the head at 1. Negative and non-finite results are treated as 0.
+#[derive(Clone, Copy, Debug)]
+pub struct WidthCurve(pub fn(f32) -> f32);
+
+impl WidthCurve {
+    /// Full width everywhere
+    pub const CONSTANT: Self = Self(|_| 1.0);
+    /// Zero width at the tail growing linearly to full width at the head
+    pub const LINEAR: Self = Self(|progress| progress);
+    /// Zero width at the tail easing in and out to full width at the head
+    pub const EASE_IN_OUT: Self = Self(|progress| progress * progress * (3.0 - 2.0 * progress));
+    /// Zero width at both ends bulging to full width in the middle
+    pub const BULGE: Self = Self(|progress| (progress * std::f32::consts::PI).sin());
+    
+    /// Multiplier at `progress`, never negative or NaN
+    fn sample(self, progress: f32) -> f32 {
+        let factor = (self.0)(progress);
+        if factor.is_finite() {
+            factor.max(0.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Shape of the ribbon's ends
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum CapStyle {
+    /// End flush with the first and last points
+    #[default]
+    None,
+    /// Round off each end with a semicircle as wide as the ribbon
+    Round,
+    /// Extend each end by half the ribbon's width
+    Square,
+}
+
+/// Number of triangles in a round cap's fan
+const ROUND_CAP_SEGMENTS: usize = 8;
+
+impl CapStyle {
+    /// Outline of a cap from the `left` to the `right` edge, bulging by
+    /// `radius` along `outward`
+    fn outline(self, left: Vec3, right: Vec3, outward: Vec3, radius: f32) -> Vec<Vec3> {
+        match self {
+            Self::None => Vec::new(),
+            Self::Round => {
+                let center = left.lerp(right, 0.5);
+                let across = left - center;
+                (0..=ROUND_CAP_SEGMENTS)
+                    .map(|i| {
+                        let angle = std::f32::consts::PI * i as f32 / ROUND_CAP_SEGMENTS as f32;
+                        center + across * angle.cos() + outward * radius * angle.sin()
+                    })
+                    .collect()
+            }
+            Self::Square => vec![left, left + outward * radius, right + outward * radius, right],
+        }
+    }
+}
+
+/// Periodically shrinks and regrows a trail, even while its emitter stands
+/// still, by oscillating how many points and how much age it retains
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+pub struct TrailBreathing {
+    /// Seconds for one full shrink and regrow cycle
+    pub period: f32,
+    /// Fraction of the retention limits kept at the smallest point of a cycle
+    pub min_fraction: f32,
+}
+
+impl TrailBreathing {
+    /// Fraction of the retention limits kept at `elapsed` seconds
+    fn retention(&self, elapsed: f32) -> f32 {
+        let phase = (elapsed / self.period * std::f32::consts::TAU).sin() * 0.5 + 0.5;
+        let min_fraction = self.min_fraction.clamp(0.0, 1.0);
+        min_fraction + (1.0 - min_fraction) * phase
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Trail {
+    /// Maximum number of trail points to keep. Values below 2 can never form a
+    /// ribbon and are clamped to 2 with a warning.
+    pub max_points: usize,
+    /// Maximum length of the centerline in world units. Points are dropped
+    /// from the tail until the trail is no longer than this, so it keeps the
+    /// same length whatever the speed or emission rate. Combines with
+    /// `max_points`, whichever is more restrictive wins.
+    pub max_length: Option<f32>,
+    /// How often to add new trail points (in seconds)
+    pub emit_rate: f32,
+    /// What triggers the emission of new points
+    pub emit_mode: EmitMode,
+    /// Fraction of an emission interval (0..=1) the initial timer phase is
+    /// randomly offset by, so identical trails spawned together don't pulse
+    /// in lockstep. Applied by [`Trail::with_timer_jitter`].
+    pub timer_jitter: f32,
+    /// Seed for the trail's randomised behaviour
+    pub seed: u64,
+    /// Blend between the exponentially smoothed emitter position (0) and the
+    /// raw position (1) when recording points
+    pub emit_position_blend: f32,
+    /// Rate (per second) the smoothed emitter position catches up with the
+    /// raw one, used by `emit_position_blend`
+    pub position_smoothing: f32,
+    /// When the trail is added to an already moving emitter, back-fill this
+    /// many points along its estimated velocity on the first update it can be
+    /// measured, so the trail doesn't start out as a single point
+    pub prefill_on_spawn: Option<usize>,
+    /// Width of the trail
+    pub width: f32,
+    /// Distance from the centerline to the left edge, `None` for half of
+    /// `width`. Together with `right_width` this gives asymmetric ribbons.
+    pub left_width: Option<f32>,
+    /// Distance from the centerline to the right edge, `None` for half of
+    /// `width`
+    pub right_width: Option<f32>,
+    /// How the width tapers along the ribbon
+    pub taper: TaperMode,
+    /// Width multiplier along the ribbon replacing the taper, for shapes
+    /// such as flames and teardrops. Not reflected, so scenes leave it unset.
+    #[reflect(ignore)]
+    pub width_curve: Option<WidthCurve>,
+    /// Shape of the ribbon's head and tail
+    pub cap: CapStyle,
+    /// Axis the ribbon's right vector is derived from by crossing it with the
+    /// direction of travel
+    pub up_axis: Vec3,
+    /// Axis blended in when the direction of travel nears `up_axis`
+    pub fallback_axis: Vec3,
+    /// How the ribbon is oriented along its length
+    pub orient: OrientMode,
+    /// Radians the ribbon rolls about its direction of travel per world unit
+    /// of length from the tail, for helix and streamer effects
+    pub twist: f32,
+    /// Geometry generated from the points
+    pub style: TrailStyle,
+    /// Ribbon subdivisions along each segment and across the width, for
+    /// materials that displace or tessellate the surface. `(1, 1)` produces
+    /// the minimal two triangles per segment.
+    pub tessellation: (usize, usize),
+    /// How many times the texture repeats along the ribbon
+    pub uv_tiling: f32,
+    /// Texture repeats per second the V coordinate scrolls towards the tail,
+    /// for flowing energy effects
+    pub uv_scroll_speed: f32,
+    /// Limit the inner edge's width to the local turn radius so the ribbon
+    /// never folds through itself on hairpin turns
+    pub clamp_to_curvature: bool,
+    /// Turn the ribbon towards the camera at every point instead of deriving
+    /// its orientation from `up_axis`, so it never shows up edge-on
+    pub face_camera: bool,
+    /// Seconds a point lives before it expires, `None` to keep points until
+    /// `max_points` pushes them out
+    pub max_age: Option<f32>,
+    /// Expire points after this many simulation frames instead of relying on
+    /// wall-clock age. Frames are counted per trail in `update_trails`, which
+    /// keeps expiry deterministic across machines running in lockstep.
+    pub max_age_frames: Option<u64>,
+    /// Cyclically shrink and regrow the trail for ambient breathing effects
+    pub breathing: Option<TrailBreathing>,
+    /// Whether to build a mesh for this trail. Data-only trails still record
+    /// and expire points but never spawn a mesh entity.
+    pub render: bool,
+    /// Freeze the trail, neither emitting nor expiring points while the mesh
+    /// stays visible. The trail's clock stops too, so points resume ageing
+    /// where they left off.
+    pub paused: bool,
+    /// Cap how many times per second the mesh is rebuilt. `None` rebuilds
+    /// every frame.
+    pub mesh_update_hz: Option<f32>,
+    /// With `mesh_update_hz` set, still rebuild at least every this many
+    /// frames so important trails never look too stale
+    pub max_stale_frames: Option<u32>,
+    /// Trails with a higher priority are rebuilt first when the
+    /// [`TrailRebuildBudget`] is exceeded and stay active first under the
+    /// [`TrailBudget`]
+    pub priority: i32,
+    /// Write [`ATTRIBUTE_EDGE_DISTANCE`] into ribbon meshes for soft-edged
+    /// materials such as [`SoftEdgeMaterial`]
+    pub edge_attribute: bool,
+    /// Build ribbons as an unindexed `TriangleStrip` instead of an indexed
+    /// `TriangleList`, dropping the index buffer for long trails. The vertices
+    /// and winding are the same either way, so lighting is unchanged. Only
+    /// applies to ribbons without tessellation across the width or caps.
+    pub triangle_strip: bool,
+    /// Draw this trail as part of one shared mesh per material instead of on
+    /// its own mesh entity, cutting draw calls when many trails share a
+    /// material. Batched trails are always drawn as triangle lists on the
+    /// default render layers.
+    pub batched: bool,
+    /// Keep the mesh entity's bounding box in step with the geometry so it is
+    /// frustum culled correctly. Disable to never cull the trail instead.
+    pub frustum_culling: bool,
+    /// Fade the mesh out over this many seconds when the trail empties or is
+    /// removed, instead of despawning it right away. See [`TrailFadeOut`].
+    pub fade_out: Option<f32>,
+    /// Simplify the rendered points with Ramer-Douglas-Peucker, dropping points
+    /// that deviate less than this distance from a straight line. The stored
+    /// points are left untouched.
+    pub simplify_tolerance: Option<f32>,
+    /// Extra points interpolated between each pair of stored points along a
+    /// Catmull-Rom spline, so low emission rates still render smooth curves
+    pub smoothing: usize,
+    /// Fade each point's vertex alpha out over this many seconds of age, so
+    /// points dissolve at the same real-time rate whatever the frame rate or
+    /// point count
+    pub fade_duration: Option<f32>,
+    /// Fade each point's vertex alpha out as it approaches `max_age`, so the
+    /// tail dissolves before its points expire instead of being cut off
+    pub fade_with_age: bool,
+    /// Vertex colors from the tail to the head, modulating the material's
+    /// `base_color` (leave it white for the exact gradient). A transparent
+    /// tail color dissolves the trail smoothly instead of popping as points
+    /// expire.
+    pub color_gradient: Option<(Color, Color)>,
+    /// New points closer than this to the head refresh its timestamp instead
+    /// of adding a zero-length segment
+    pub merge_epsilon: f32,
+    /// Skip emission until the emitter is at least this far from the newest
+    /// point, so a stopped emitter's trail drains away instead of piling up
+    pub min_movement: f32,
+    /// Extend the rendered ribbon from the newest point to the emitter's
+    /// current position every frame, so the trail stays attached to it
+    /// between emissions instead of snapping forward. The extra vertex is
+    /// never stored as a point. Only ribbons emitting on their own attach
+    /// their head, never with [`EmitMode::Manual`] or while over the
+    /// [`TrailBudget`], since those don't follow the emitter. Off by default.
+    pub attach_head: bool,
+    /// Material handle for the trail. Add a [`TrailMaterial`] to draw it with
+    /// a custom material instead.
+    pub material: Handle<StandardMaterial>,
+    /// Switch `material` to double-sided rendering without backface culling,
+    /// so the ribbon stays visible and correctly lit from behind. Back faces
+    /// are shaded with their normal flipped. Materials shared with other
+    /// meshes are changed for those too. Ignored with a warning while a
+    /// [`TrailMaterial`] draws the trail.
+    pub double_sided: bool,
+    /// Shared config asset whose values are applied to this trail when it
+    /// loads or changes
+    pub config: Option<Handle<TrailConfigAsset>>,
+    /// Render layers for the mesh entity. To composite trails in their own
+    /// pass, put them on a dedicated layer, render that layer with a second
+    /// camera whose target is an offscreen image (see [`trail_render_target`])
+    /// and leave the layer out of the main camera's `RenderLayers`.
+    pub render_layers: Option<RenderLayers>,
+    /// Entity whose frame the trail is recorded and rendered relative to.
+    /// Points are stored in that entity's local space at emission time and the
+    /// mesh follows its `GlobalTransform`, so the trail moves with the frame
+    /// even though the frame and the emitter differ.
+    pub reference_frame: Option<Entity>,
+    /// Space the points are recorded in
+    pub space: TrailSpace,
+    /// Emit from the world position in `GlobalTransform`, extrapolated by the
+    /// emitter's combined world velocity, instead of the local `Transform`.
+    /// Trails on children of moving platforms then account for the parent's
+    /// motion instead of lagging a frame behind it.
+    pub inherit_velocity: bool,
+    /// Sorted `(progress, width)` pairs replacing the taper, see
+    /// [`Trail::set_width_keyframes`]
+    pub(crate) width_keyframes: Vec<(f32, f32)>,
+    /// Shared width, alpha and color curves sampled along the ribbon. Width
+    /// keyframes on the trail itself take precedence over the profile's.
+    pub profile: Option<Handle<TrailProfile>>,
+    /// Emitter's parent, tracked for `TrailSpace::Local`
+    #[reflect(ignore)]
+    pub(crate) local_parent: Option<Entity>,
+    /// Internal timer for emission
+    pub(crate) timer: Timer,
+    /// Stored trail points
+    pub(crate) points: VecDeque<TrailPoint>,
+    /// Mesh generated for the current frame, if there is anything to draw
+    #[reflect(ignore)]
+    pub(crate) mesh: Option<Handle<Mesh>>,
+    /// Generated mesh entity
+    #[reflect(ignore)]
+    pub(crate) mesh_entity: Option<Entity>,
+    /// Hide rather than despawn the mesh entity while there is nothing to
+    /// draw, set by [`Trail::reset`]
+    pub(crate) keep_mesh_entity: bool,
+    /// Number of frames this trail has been updated for
+    pub(crate) frame: u64,
+    /// Seconds this trail has been updated for, used to timestamp points
+    pub(crate) clock: f32,
+    /// `BeatClock` count seen on the previous update, `None` until the first
+    /// update so a trail spawned after the clock has pulsed doesn't emit
+    pub(crate) last_beat: Option<u64>,
+    /// Emitter position on the latest update, rendered as the head with
+    /// `attach_head`
+    pub(crate) emitter_position: Option<Vec3>,
+    /// Whether the latest update rendered the emitter as the head
+    pub(crate) head_attached: bool,
+    /// Exponentially smoothed emitter position
+    pub(crate) smoothed_position: Option<Vec3>,
+    /// Raw emitter position on the previous update
+    pub(crate) last_raw_position: Option<Vec3>,
+    /// World position from `GlobalTransform` on the previous update
+    pub(crate) last_world_position: Option<Vec3>,
+    /// World velocity measured from `GlobalTransform` deltas
+    pub(crate) world_velocity: Vec3,
+    /// Whether `prefill_on_spawn` has been applied
+    pub(crate) prefilled: bool,
+    /// Whether the [`TrailBudget`] currently stops this trail from emitting
+    pub(crate) over_budget: bool,
+    /// Set whenever the rendered geometry changes, cleared after a rebuild
+    pub(crate) dirty: bool,
+    /// Seconds since the mesh was last rebuilt
+    pub(crate) since_rebuild: f32,
+    /// Frames since the mesh was last rebuilt
+    pub(crate) stale_frames: u32,
+    /// Config asset most recently applied to this trail
+    #[reflect(ignore)]
+    pub(crate) applied_config: Option<AssetId<TrailConfigAsset>>,
+    /// Whether a [`TrailMaterial`] draws the mesh instead of `material`
+    #[reflect(ignore)]
+    pub(crate) custom_material: bool,
+    /// Bounds of the latest rebuilt mesh, until they're applied to the mesh
+    /// entity
+    #[reflect(ignore)]
+    pub(crate) mesh_bounds: Option<Aabb>,
+    /// Rebuilt since its batch was last merged
+    pub(crate) batch_dirty: bool,
+}
+
+#[derive(Clone, Reflect)]
+struct TrailPoint {
+    position: Vec3,
+    timestamp: f32,
+    frame: u64,
+    /// Right vector parallel transported along the stored points up to this
+    /// one when it was recorded, for [`OrientMode::ParallelTransport`]
+    right: Option<Vec3>,
+}
+
+impl TrailPoint {
+    fn is_finite(&self) -> bool {
+        self.position.is_finite() && self.timestamp.is_finite()
+    }
+}
+
+impl Trail {
+    pub fn new(
+        max_points: usize,
+        emit_rate: f32,
+        width: f32,
+        material: Handle<StandardMaterial>,
+    ) -> Self {
+        Self {
+            max_points,
+            max_length: None,
+            emit_rate,
+            emit_mode: EmitMode::Timer,
+            timer_jitter: 0.0,
+            seed: 0,
+            emit_position_blend: 1.0,
+            position_smoothing: 10.0,
+            prefill_on_spawn: None,
+            width,
+            left_width: None,
+            right_width: None,
+            taper: TaperMode::TailToHead,
+            width_curve: None,
+            cap: CapStyle::None,
+            up_axis: Vec3::Y,
+            fallback_axis: Vec3::X,
+            orient: OrientMode::Adaptive,
+            twist: 0.0,
+            style: TrailStyle::Ribbon,
+            tessellation: (1, 1),
+            uv_tiling: 1.0,
+            uv_scroll_speed: 0.0,
+            clamp_to_curvature: false,
+            face_camera: false,
+            max_age: Some(DEFAULT_MAX_AGE),
+            max_age_frames: None,
+            breathing: None,
+            render: true,
+            paused: false,
+            mesh_update_hz: None,
+            max_stale_frames: None,
+            priority: 0,
+            edge_attribute: false,
+            triangle_strip: false,
+            batched: false,
+            frustum_culling: true,
+            fade_out: None,
+            simplify_tolerance: None,
+            smoothing: 0,
+            fade_duration: None,
+            fade_with_age: false,
+            color_gradient: None,
+            merge_epsilon: DEFAULT_MERGE_EPSILON,
+            min_movement: 0.0,
+            attach_head: false,
+            material,
+            double_sided: false,
+            config: None,
+            render_layers: None,
+            reference_frame: None,
+            space: TrailSpace::World,
+            inherit_velocity: false,
+            width_keyframes: Vec::new(),
+            profile: None,
+            local_parent: None,
+            timer: Timer::from_seconds(1.0 / emit_rate, TimerMode::Repeating),
+            points: VecDeque::new(),
+            mesh: None,
+            mesh_entity: None,
+            keep_mesh_entity: false,
+            frame: 0,
+            clock: 0.0,
+            last_beat: None,
+            emitter_position: None,
+            head_attached: false,
+            smoothed_position: None,
+            last_raw_position: None,
+            last_world_position: None,
+            world_velocity: Vec3::ZERO,
+            prefilled: false,
+            over_budget: false,
+            dirty: true,
+            since_rebuild: 0.0,
+            stale_frames: 0,
+            applied_config: None,
+            custom_material: false,
+            mesh_bounds: None,
+            batch_dirty: false,
+        }
+    }
+
+    /// Start building a trail from defaults, see [`TrailBuilder`]
+    pub fn builder() -> TrailBuilder {
+        TrailBuilder::default()
+    }
+
+    /// Offset the emission timer's starting phase by a random fraction of up to
+    /// `jitter` of one interval, derived deterministically from `seed`
+    pub fn with_timer_jitter(mut self, jitter: f32, seed: u64) -> Self {
+        self.timer_jitter = jitter.clamp(0.0, 1.0);
+        self.seed = seed;
+        let phase = self.timer_jitter * unit_random(seed);
+        let offset = self.timer.duration().mul_f32(phase);
+        self.timer.set_elapsed(offset);
+        self
+    }
+
+    /// Set how many seconds points live, `None` to only expire them through
+    /// `max_points`
+    pub fn with_max_age(mut self, max_age: Option<f32>) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Record a point directly, independent of the emission mode.
+    ///
+    /// `timestamp` is on the trail's own clock, see [`Trail::clock`].
+    pub fn push_point(&mut self, position: Vec3, timestamp: f32) {
+        let frame = self.frame;
+        self.record_point(TrailPoint {
+            position,
+            timestamp,
+            frame,
+            right: None,
+        });
+    }
+
+    /// Record a whole path from the tail to the head at once, e.g. a
+    /// predetermined arc, with timestamps ending at the current clock. Points
+    /// are back-dated `1 / emit_rate` apart, or closer when the path wouldn't
+    /// fit within `max_age` otherwise, so it isn't cut short on the next
+    /// update and expires from the tail like an emitted trail. Without a
+    /// positive `emit_rate` every point gets the current clock.
+    /// `max_points` still applies, keeping and spacing only the newest points.
+    pub fn push_path(&mut self, path: impl IntoIterator<Item = Vec3>) {
+        let path: Vec<Vec3> = path.into_iter().collect();
+        // Older points would be dropped right away, so don't spend age on them
+        let kept = &path[path.len().saturating_sub(self.max_points.max(1))..];
+        let Some(last) = kept.len().checked_sub(1) else {
+            return;
+        };
+        let mut interval = if self.emit_rate > 0.0 { 1.0 / self.emit_rate } else { 0.0 };
+        if let (Some(max_age), true) = (self.max_age, last > 0) {
+            interval = interval.min(max_age / last as f32);
+        }
+        for (i, &position) in kept.iter().enumerate() {
+            let age = (last - i) as f32 * interval;
+            self.push_point(position, self.clock - age);
+        }
+    }
+
+    /// Seconds the trail has been updated for, the time base of its points
+    pub fn clock(&self) -> f32 {
+        self.clock
+    }
+
+    /// Direction of travel at the head, from the second newest to the newest
+    /// point. `None` with fewer than two distinct points.
+    pub fn head_direction(&self) -> Option<Vec3> {
+        let mut newest = self.points.iter().rev();
+        let head = newest.next()?;
+        let previous = newest.next()?;
+        (head.position - previous.position).try_normalize()
+    }
+
+    /// Number of stored points
+    pub fn point_count(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Position of the newest point
+    pub fn head_position(&self) -> Option<Vec3> {
+        self.points.back().map(|point| point.position)
+    }
+
+    /// Position of the oldest point
+    pub fn tail_position(&self) -> Option<Vec3> {
+        self.points.front().map(|point| point.position)
+    }
+
+    /// Positions of the stored points from the tail to the head, in the space
+    /// they're recorded in
+    pub fn positions(&self) -> impl Iterator<Item = Vec3> + '_ {
+        self.points.iter().map(|point| point.position)
+    }
+
+    /// Entity currently drawing the trail's mesh, if any
+    pub fn mesh_entity(&self) -> Option<Entity> {
+        self.mesh_entity
+    }
+
+    /// Total length of the trail's centerline
+    pub fn length(&self) -> f32 {
+        self.points
+            .iter()
+            .zip(self.points.iter().skip(1))
+            .map(|(a, b)| a.position.distance(b.position))
+            .sum()
+    }
+
+    /// Arc length of the stored points from the tail to the head, the same
+    /// as [`Trail::length`]. The head rendered with `attach_head` isn't
+    /// included.
+    pub fn arc_length(&self) -> f32 {
+        self.length()
+    }
+
+    /// `count` positions evenly spaced by arc length along the centerline,
+    /// from the tail to the head. A single stored point is repeated `count`
+    /// times and an empty trail gives no positions.
+    pub fn resample(&self, count: usize) -> Vec<Vec3> {
+        let Some(tail) = self.points.front() else {
+            return Vec::new();
+        };
+        let length = self.length();
+        if count < 2 || length <= 0.0 {
+            return vec![tail.position; count];
+        }
+        
+        let spacing = length / (count - 1) as f32;
+        let mut samples = Vec::with_capacity(count);
+        let mut segments = self.points.iter().zip(self.points.iter().skip(1));
+        let mut segment = segments.next();
+        // Arc length at the start of the current segment
+        let mut start = 0.0;
+        for i in 0..count {
+            let target = i as f32 * spacing;
+            while let Some((a, b)) = segment {
+                let segment_length = a.position.distance(b.position);
+                if target <= start + segment_length {
+                    break;
+                }
+                start += segment_length;
+                segment = segments.next();
+            }
+            let position = match segment {
+                Some((a, b)) => {
+                    let segment_length = a.position.distance(b.position);
+                    let t = if segment_length > 0.0 { (target - start) / segment_length } else { 0.0 };
+                    a.position.lerp(b.position, t.clamp(0.0, 1.0))
+                }
+                // Rounding can push the last target just past the head
+                None => self.points.back().map_or(tail.position, |head| head.position),
+            };
+            samples.push(position);
+        }
+        
+        samples
+    }
+
+    /// Position at `t` of the way along the centerline by arc length, from
+    /// the tail at 0 to the head at 1. `None` for an empty trail.
+    pub fn sample_at(&self, t: f32) -> Option<Vec3> {
+        let (a, b, fraction) = self.locate(t)?;
+        Some(a.lerp(b, fraction))
+    }
+
+    /// Direction of travel at `t` of the way along the centerline, see
+    /// [`Trail::sample_at`]. `None` with fewer than two distinct points.
+    pub fn direction_at(&self, t: f32) -> Option<Vec3> {
+        let (a, b, _) = self.locate(t)?;
+        (b - a).try_normalize().or_else(|| self.head_direction())
+    }
+
+    /// Segment end points and the fraction between them at arc length
+    /// fraction `t`
+    fn locate(&self, t: f32) -> Option<(Vec3, Vec3, f32)> {
+        let tail = self.points.front()?.position;
+        let target = self.length() * t.clamp(0.0, 1.0);
+        let mut start = 0.0;
+        let mut last_segment = (tail, tail);
+        for (a, b) in self.points.iter().zip(self.points.iter().skip(1)) {
+            let segment_length = a.position.distance(b.position);
+            if segment_length <= 0.0 {
+                continue;
+            }
+            last_segment = (a.position, b.position);
+            if target <= start + segment_length {
+                return Some((a.position, b.position, (target - start) / segment_length));
+            }
+            start += segment_length;
+        }
+        Some((last_segment.0, last_segment.1, 1.0))
+    }
+
+    /// Change how many points are emitted per second. Rates that aren't
+    /// positive and finite are ignored with a warning, keeping the current one.
+    pub fn set_emit_rate(&mut self, emit_rate: f32) {
+        if !(emit_rate.is_finite() && emit_rate > 0.0) {
+            warn!("Ignoring trail emit_rate of {emit_rate}, it must be positive and finite");
+            return;
+        }
+        self.emit_rate = emit_rate;
+        self.timer.set_duration(Duration::from_secs_f32(1.0 / emit_rate));
+    }
+
+    /// Change the trail width, regenerating the mesh on the next update
+    pub fn set_width(&mut self, width: f32) {
+        self.width = width;
+        self.dirty = true;
+    }
+
+    /// Author the width along the trail as `(progress, width)` keyframes, with
+    /// progress running from 0 at the tail to 1 at the head. Widths are
+    /// interpolated linearly and held past the first and last keyframe. An
+    /// empty list restores the default taper. Keyframes are sorted by
+    /// progress, and ones with a non-finite progress are dropped.
+    pub fn set_width_keyframes(&mut self, keyframes: impl Into<Vec<(f32, f32)>>) {
+        let mut keyframes = keyframes.into();
+        sort_keyframes(&mut keyframes);
+        self.width_keyframes = keyframes;
+        self.dirty = true;
+    }
+
+    /// Width keyframes set with [`Trail::set_width_keyframes`]
+    pub fn width_keyframes(&self) -> &[(f32, f32)] {
+        &self.width_keyframes
+    }
+
+    /// Distances from the centerline to the left and right ribbon edges at
+    /// `progress`
+    fn edge_widths(&self, progress: f32, profile: Option<&TrailProfile>) -> (f32, f32) {
+        let half_width = self.width * 0.5;
+        let left_width = self.left_width.unwrap_or(half_width);
+        let right_width = self.right_width.unwrap_or(half_width);
+        
+        // Calculate width based on progress
+        let taper = match self.width_curve {
+            Some(curve) => curve.sample(progress),
+            None => self.taper.factor(progress),
+        };
+        
+        // Keyframed widths keep the left/right split of the default widths
+        let keyframed = self
+            .keyframed_width(progress)
+            .or_else(|| profile.and_then(|profile| profile.width_at(progress)));
+        match keyframed {
+            Some(width) => {
+                let total = left_width + right_width;
+                let left_share = if total > 0.0 { left_width / total } else { 0.5 };
+                (width * left_share, width * (1.0 - left_share))
+            }
+            None => (left_width * taper, right_width * taper),
+        }
+    }
+
+    /// Whether `point` lies on the ribbon surface, within its width of the
+    /// centerline plus `tolerance`. Positions are in the space the points are
+    /// stored in, i.e. the reference frame's when one is set.
+    pub fn contains_point(&self, point: Vec3, tolerance: f32) -> bool {
+        if self.points.len() == 1 {
+            return self.points[0].position.distance(point) <= tolerance;
+        }
+        let last = self.points.len().saturating_sub(1) as f32;
+        self.points
+            .iter()
+            .zip(self.points.iter().skip(1))
+            .enumerate()
+            .any(|(i, (a, b))| {
+                // Test against the wider end, as the width varies along it
+                let (start_left, start_right) = self.edge_widths(i as f32 / last, None);
+                let (end_left, end_right) = self.edge_widths((i + 1) as f32 / last, None);
+                let band = start_left.max(start_right).max(end_left).max(end_right);
+                distance_to_segment(point, a.position, b.position) <= band + tolerance
+            })
+    }
+
+    /// Width from the keyframes at `progress`, `None` without keyframes
+    fn keyframed_width(&self, progress: f32) -> Option<f32> {
+        sample_keyframes(&self.width_keyframes, progress, |a, b, t| a + (b - a) * t)
+    }
+
+    /// Shift every stored point by `delta`, e.g. to carry a laid trail along
+    /// with a moving platform
+    pub fn translate(&mut self, delta: Vec3) {
+        for point in self.points.iter_mut() {
+            point.position += delta;
+        }
+        self.dirty = true;
+    }
+
+    /// World velocity of the emitter, measured while `inherit_velocity` is set
+    pub fn world_velocity(&self) -> Vec3 {
+        self.world_velocity
+    }
+
+    /// Drop every point, with the mesh entity despawned on the next update.
+    /// Unlike [`Trail::reset`] the emission state is left as it is.
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.keep_mesh_entity = false;
+        self.dirty = true;
+    }
+
+    /// Drop every point and restart emission while keeping the settings and
+    /// the mesh entity, which stays hidden until the trail has enough points
+    /// again. The next point starts a fresh trail with no segment back to the
+    /// old ones, e.g. when a pooled emitter is respawned elsewhere.
+    pub fn reset(&mut self) {
+        self.points.clear();
+        self.timer.reset();
+        self.emitter_position = None;
+        self.smoothed_position = None;
+        self.last_raw_position = None;
+        self.last_world_position = None;
+        self.keep_mesh_entity = self.mesh_entity.is_some();
+        self.dirty = true;
+    }
+
+    /// Hand the current mesh entity over to the caller, e.g. to leave an
+    /// after-image behind, and start a fresh trail. The trail no longer
+    /// updates or despawns the returned entity, whose mesh stays frozen as it
+    /// is now. Returns `None` and leaves the trail untouched while it has no
+    /// mesh entity.
+    pub fn detach(&mut self) -> Option<Entity> {
+        let mesh_entity = self.mesh_entity.take()?;
+        // Forget the mesh too, it would otherwise be overwritten in place
+        self.mesh = None;
+        self.points.clear();
+        self.keep_mesh_entity = false;
+        self.dirty = true;
+        Some(mesh_entity)
+    }
+
+    /// Whether the [`TrailBudget`] currently stops this trail from emitting
+    pub fn is_over_budget(&self) -> bool {
+        self.over_budget
+    }
+
+    /// Whether the mesh is out of date with the trail's points or settings
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Rebuild the mesh on the next update. Meshes are only rebuilt when the
+    /// points change, so call this after changing fields such as `width` or
+    /// `color_gradient` directly.
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Append a point, dropping the oldest ones beyond `max_points`. Returns
+    /// whether a new point was added rather than dropped or merged.
+    fn record_point(&mut self, mut point: TrailPoint) -> bool {
+        if !point.is_finite() {
+            return false;
+        }
+        if let Some(head) = self.points.back_mut() {
+            if head.position.distance(point.position) <= self.merge_epsilon {
+                head.timestamp = point.timestamp;
+                head.frame = point.frame;
+                return false;
+            }
+            
+            // Transport the head's right vector onto the new segment, seeding
+            // it from the first segment the trail has
+            if let Some(forward) = (point.position - head.position).try_normalize() {
+                let right = head
+                    .right
+                    .unwrap_or_else(|| ribbon_right(forward, self.up_axis, self.fallback_axis));
+                point.right = right.reject_from_normalized(forward).try_normalize();
+                head.right = head.right.or(point.right);
+            }
+        }
+        self.points.push_back(point);
+        self.trim_to(self.max_points);
+        self.dirty = true;
+        true
+    }
+
+    /// Drop the oldest points until at most `max_points` remain
+    fn trim_to(&mut self, max_points: usize) {
+        while self.points.len() > max_points {
+            self.points.pop_front();
+            self.dirty = true;
+        }
+    }
+
+    /// Drop the oldest points until the centerline is at most `max_length`
+    /// long
+    fn trim_to_length(&mut self, max_length: f32) {
+        let mut length = self.length();
+        while length > max_length && self.points.len() > 1 {
+            let Some(tail) = self.points.pop_front() else {
+                break;
+            };
+            length -= tail.position.distance(self.points[0].position);
+            self.dirty = true;
+        }
+    }
+
+    /// Advance the emission timer and the trail's clocks by `delta`. Returns
+    /// whether the timer fired.
+    fn tick(&mut self, delta: Duration) -> bool {
+        self.timer.tick(delta);
+        self.frame += 1;
+        self.clock += delta.as_secs_f32();
+        self.timer.just_finished()
+    }
+
+    /// Drop points older than `max_age`, scaled by `retention`, or than
+    /// `max_age_frames`
+    fn expire_aged(&mut self, retention: f32) {
+        // Remove points that are too old (optional fade-out based on time)
+        if let Some(max_age) = self.max_age {
+            let current_time = self.clock;
+            let max_age = max_age * retention;
+            self.expire_points(|point| current_time - point.timestamp > max_age);
+        }
+        
+        // Remove points that have outlived their frame budget
+        if let Some(max_age_frames) = self.max_age_frames {
+            let current_frame = self.frame;
+            self.expire_points(|point| current_frame - point.frame > max_age_frames);
+        }
+    }
+
+    /// Drop points from the tail for as long as `expired` holds
+    fn expire_points(&mut self, expired: impl Fn(&TrailPoint) -> bool) {
+        while let Some(front) = self.points.front() {
+            if expired(front) {
+                self.points.pop_front();
+                self.dirty = true;
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Build a standalone mesh from the trail's current points.
+    ///
+    /// Camera-facing styles face down the -Z axis since no view is known.
+    pub fn to_mesh(&self) -> Mesh {
+        self.build_mesh(None, None)
+    }
+
+    /// Build the trail mesh, facing camera-aligned geometry towards `view`
+    fn build_mesh(&self, view: Option<&GlobalTransform>, profile: Option<&TrailProfile>) -> Mesh {
+        let points = self.render_points();
+        match self.style {
+            TrailStyle::Ribbon => {
+                let camera = view.map(GlobalTransform::translation);
+                create_trail_mesh(&points, self, profile, camera)
+            }
+            TrailStyle::Quads { size_over_age } => {
+                let (right, up) = view
+                    .map(|view| (view.right().as_vec3(), view.up().as_vec3()))
+                    .unwrap_or((Vec3::X, Vec3::Y));
+                create_quad_mesh(&points, self.width, self.clock, right, up, |age| {
+                    let scale = (1.0 - age * size_over_age).clamp(0.0, 1.0);
+                    (scale, scale * self.fade_alpha(age))
+                })
+            }
+            TrailStyle::SoftPuffs { radius_over_age } => {
+                let (right, up) = view
+                    .map(|view| (view.right().as_vec3(), view.up().as_vec3()))
+                    .unwrap_or((Vec3::X, Vec3::Y));
+                create_quad_mesh(&points, self.width, self.clock, right, up, |age| {
+                    let scale = 1.0 + age * radius_over_age;
+                    let alpha = self
+                        .max_age
+                        .map_or(1.0, |max_age| (1.0 - age / max_age).clamp(0.0, 1.0));
+                    (scale.max(0.0), alpha * self.fade_alpha(age))
+                })
+            }
+        }
+    }
+
+    /// Whether points fade out with age under `fade_duration` or
+    /// `fade_with_age`
+    fn fades(&self) -> bool {
+        self.fade_duration.is_some() || (self.fade_with_age && self.max_age.is_some())
+    }
+
+    /// Whether the mesh changes over time or with the view even while the
+    /// points stay the same, so it has to be rebuilt every update
+    fn animated(&self) -> bool {
+        self.fades()
+            || self.uv_scroll_speed != 0.0
+            || self.face_camera
+            || self.style != TrailStyle::Ribbon
+    }
+
+    /// Alpha of a point `age` seconds old under `fade_duration` and
+    /// `fade_with_age`
+    fn fade_alpha(&self, age: f32) -> f32 {
+        let duration_fade = match self.fade_duration {
+            Some(duration) if duration > 0.0 => 1.0 - (age / duration).clamp(0.0, 1.0),
+            Some(_) => 0.0,
+            None => 1.0,
+        };
+        let age_fade = match (self.fade_with_age, self.max_age) {
+            (true, Some(max_age)) if max_age > 0.0 => 1.0 - (age / max_age).clamp(0.0, 1.0),
+            (true, Some(_)) => 0.0,
+            _ => 1.0,
+        };
+        duration_fade * age_fade
+    }
+
+    /// Entity whose space the points are stored in, if any
+    pub(crate) fn frame_entity(&self) -> Option<Entity> {
+        match self.space {
+            TrailSpace::World => self.reference_frame,
+            TrailSpace::Local => self.local_parent,
+        }
+    }
+
+    /// Whether moving to `position` turns away from the last segment by more
+    /// than `angle_threshold` or strays `max_distance` from the newest point
+    fn turned(&self, position: Vec3, angle_threshold: f32, max_distance: f32) -> bool {
+        let Some(head) = self.points.back() else {
+            return true;
+        };
+        let offset = position - head.position;
+        let distance = offset.length();
+        if distance >= max_distance {
+            return true;
+        }
+        // Ignore jitter below the merge distance
+        if distance <= self.merge_epsilon {
+            return false;
+        }
+        match self.head_direction() {
+            Some(direction) => direction.angle_between(offset / distance) > angle_threshold,
+            None => true,
+        }
+    }
+
+    /// Minimum number of points needed to produce any geometry
+    fn min_render_points(&self) -> usize {
+        match self.style {
+            TrailStyle::Ribbon => 2,
+            TrailStyle::Quads { .. } | TrailStyle::SoftPuffs { .. } => 1,
+        }
+    }
+
+    /// Points the mesh is generated from, after optional simplification and
+    /// smoothing
+    fn render_points(&self) -> Cow<'_, VecDeque<TrailPoint>> {
+        // Never let a non-finite point that slipped in reach the vertices
+        let mut points = if self.points.iter().all(TrailPoint::is_finite) {
+            Cow::Borrowed(&self.points)
+        } else {
+            Cow::Owned(self.points.iter().filter(|point| point.is_finite()).cloned().collect())
+        };
+        // Connect the ribbon to the emitter ahead of the newest point
+        if let (true, Some(position), Some(newest)) =
+            (self.head_attached, self.emitter_position, self.points.back())
+        {
+            if newest.position.distance(position) > self.merge_epsilon {
+                points.to_mut().push_back(TrailPoint {
+                    position,
+                    timestamp: self.clock,
+                    frame: self.frame,
+                    right: None,
+                });
+            }
+        }
+        let points = match self.simplify_tolerance {
+            Some(tolerance) => Cow::Owned(simplify_points(&points, tolerance)),
+            None => points,
+        };
+        match self.smoothing {
+            0 => points,
+            subdivisions => Cow::Owned(catmull_rom_points(&points, subdivisions)),
+        }
+    }
+
+    /// Replace the trail attributes and indices of a mesh you own with the
+    /// trail's current geometry, e.g. for a custom render setup that doesn't
+    /// use the crate's mesh entity. Attributes the trail doesn't generate are
+    /// left as they are, so keep their lengths in step yourself. `mesh` should
+    /// use the topology the trail generates, a triangle strip with
+    /// `triangle_strip` set and a triangle list otherwise.
+    pub fn write_into(&self, mesh: &mut Mesh) {
+        let mut generated = self.to_mesh();
+        for attribute in TRAIL_ATTRIBUTES {
+            match generated.remove_attribute(attribute.id) {
+                Some(values) => mesh.insert_attribute(attribute, values),
+                None => {
+                    mesh.remove_attribute(attribute.id);
+                }
+            }
+        }
+        match generated.remove_indices() {
+            Some(indices) => mesh.insert_indices(indices),
+            None => {
+                mesh.remove_indices();
+            }
+        }
+    }
+
+    /// Export the trail's current geometry as a Wavefront OBJ string
+    pub fn export_obj(&self) -> String {
+        mesh_to_obj(&self.to_mesh())
+    }
+}
+
+/// Sort `(progress, value)` keyframes by progress, dropping ones with a
+/// non-finite progress
+fn sort_keyframes<T>(keyframes: &mut Vec<(f32, T)>) {
+    keyframes.retain(|(progress, _)| progress.is_finite());
+    keyframes.sort_by(|a, b| a.0.total_cmp(&b.0));
+}
+
+/// Interpolate sorted `(progress, value)` keyframes at `progress`, holding the
+/// first and last value outside their range. `None` without keyframes.
+fn sample_keyframes<T: Copy>(
+    keyframes: &[(f32, T)],
+    progress: f32,
+    lerp: impl Fn(T, T, f32) -> T,
+) -> Option<T> {
+    let first = keyframes.first()?;
+    let last = keyframes.last()?;
+    if progress <= first.0 {
+        return Some(first.1);
+    }
+    if progress >= last.0 {
+        return Some(last.1);
+    }
+    keyframes.windows(2).find_map(|pair| {
+        let ((p0, v0), (p1, v1)) = (pair[0], pair[1]);
+        (progress <= p1).then(|| {
+            let span = p1 - p0;
+            let t = if span > 0.0 { (progress - p0) / span } else { 1.0 };
+            lerp(v0, v1, t)
+        })
+    })
+}
+
+/// Every vertex attribute trail meshes may carry
+const TRAIL_ATTRIBUTES: [MeshVertexAttribute; 5] = [
+    Mesh::ATTRIBUTE_POSITION,
+    Mesh::ATTRIBUTE_NORMAL,
+    Mesh::ATTRIBUTE_UV_0,
+    Mesh::ATTRIBUTE_COLOR,
+    ATTRIBUTE_EDGE_DISTANCE,
+];
+
+/// Smallest `max_points` that can still produce a ribbon
+const MIN_MAX_POINTS: usize = 2;
+
+/// Default [`Trail::max_age`]
+const DEFAULT_MAX_AGE: f32 = 5.0;
+
+/// Default [`Trail::merge_epsilon`]
+const DEFAULT_MERGE_EPSILON: f32 = 1e-4;
+
+/// SplitMix64 hash of `seed` mapped to `[0, 1)`
+fn unit_random(seed: u64) -> f32 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Keep only the most important trails emitting when over the [`TrailBudget`]
+fn apply_trail_budget(
+    budget: Res<TrailBudget>,
+    mut trail_query: Query<(Entity, &mut Trail, Option<&GlobalTransform>)>,
+    camera_query: Query<&GlobalTransform, With<Camera3d>>,
+) {
+    if trail_query.iter().len() <= budget.max_active {
+        for (_, mut trail, _) in trail_query.iter_mut() {
+            if trail.over_budget {
+                trail.over_budget = false;
+            }
+        }
+        return;
+    }
+    
+    let camera_position = camera_query.iter().next().map(GlobalTransform::translation);
+    let mut ranked: Vec<_> = trail_query
+        .iter()
+        .map(|(entity, trail, transform)| {
+            let distance = match (camera_position, transform) {
+                (Some(camera), Some(transform)) => transform.translation().distance_squared(camera),
+                _ => 0.0,
+            };
+            (entity, trail.priority, distance)
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.2.total_cmp(&b.2)));
+    
+    for (rank, (entity, _, _)) in ranked.into_iter().enumerate() {
+        if let Ok((_, mut trail, _)) = trail_query.get_mut(entity) {
+            let over_budget = rank >= budget.max_active;
+            if trail.over_budget != over_budget {
+                trail.over_budget = over_budget;
+            }
+        }
+    }
+}
+
+fn update_trails(
+    time: Res<Time>,
+    beat_clock: Res<BeatClock>,
+    mut trail_query: Query<(Entity, &mut Trail, &Transform, Option<&Parent>)>,
+    frame_query: Query<&GlobalTransform>,
+    mut emitted_events: EventWriter<TrailPointEmitted>,
+    mut warned_max_points: Local<bool>,
+    mut warned_non_finite: Local<bool>,
+) {
+    for (entity, mut trail, transform, parent) in trail_query.iter_mut() {
+        if trail.max_points < MIN_MAX_POINTS {
+            if !*warned_max_points {
+                warn!(
+                    "Trail max_points of {} can't form a trail, clamping to {}",
+                    trail.max_points, MIN_MAX_POINTS
+                );
+                *warned_max_points = true;
+            }
+            trail.max_points = MIN_MAX_POINTS;
+        }
+        
+        let local_parent = parent.map(Parent::get);
+        if trail.local_parent != local_parent {
+            trail.local_parent = local_parent;
+        }
+        
+        // Paused trails hold their points and their clock, and forget the last
+        // world position so resuming doesn't measure a velocity spike
+        if trail.paused {
+            trail.last_world_position = None;
+            continue;
+        }
+        
+        trail.tick(time.delta());
+        
+        // A local trail's translation is already relative to its parent
+        let mut sampled_in_world = trail.space == TrailSpace::World;
+        let mut raw_position = transform.translation;
+        if let (true, Ok(global)) = (trail.inherit_velocity, frame_query.get(entity)) {
+            // `GlobalTransform` is still last frame's here, so extrapolate it by
+            // the velocity of the whole hierarchy to catch up with the emitter
+            let world_position = global.translation();
+            let dt = time.delta_seconds();
+            let velocity = match trail.last_world_position {
+                Some(last_position) if dt > 0.0 && world_position.is_finite() => {
+                    (world_position - last_position) / dt
+                }
+                _ => Vec3::ZERO,
+            };
+            if world_position.is_finite() {
+                trail.last_world_position = Some(world_position);
+            }
+            trail.world_velocity = velocity;
+            raw_position = world_position + velocity * dt;
+            sampled_in_world = true;
+        }
+        if let Some(frame) = trail.frame_entity().and_then(|e| frame_query.get(e).ok()) {
+            if sampled_in_world {
+                raw_position = frame.affine().inverse().transform_point3(raw_position);
+            }
+        }
+        
+        // Hold the last valid position instead of recording NaN or infinite
+        // points that would poison the whole mesh
+        let position_valid = raw_position.is_finite();
+        if !position_valid {
+            if !*warned_non_finite {
+                warn!("Trail emitter has a non-finite position, skipping emission until it recovers");
+                *warned_non_finite = true;
+            }
+            match trail.last_raw_position {
+                Some(last_position) => raw_position = last_position,
+                None => continue,
+            }
+        }
+        
+        // Track a smoothed position every frame so blending stays continuous
+        let smoothing = 1.0 - (-trail.position_smoothing * time.delta_seconds()).exp();
+        let smoothed_position = trail
+            .smoothed_position
+            .map_or(raw_position, |smoothed| smoothed.lerp(raw_position, smoothing));
+        trail.smoothed_position = Some(smoothed_position);
+        
+        // Seed points behind an emitter that was already moving on spawn
+        if let (Some(count), false, Some(last_position)) = (
+            trail.prefill_on_spawn,
+            trail.prefilled,
+            trail.last_raw_position,
+        ) {
+            let dt = time.delta_seconds();
+            if dt > 0.0 {
+                let velocity = (raw_position - last_position) / dt;
+                if velocity.length_squared() > f32::EPSILON {
+                    let interval = 1.0 / trail.emit_rate;
+                    for step in (1..=count).rev() {
+                        let age = step as f32 * interval;
+                        let timestamp = trail.clock - age;
+                        trail.push_point(raw_position - velocity * age, timestamp);
+                    }
+                }
+                trail.prefilled = true;
+            }
+        }
+        trail.last_raw_position = Some(raw_position);
+        
+        let position = smoothed_position.lerp(raw_position, trail.emit_position_blend);
+        let should_emit = match trail.emit_mode {
+            EmitMode::Timer => trail.timer.just_finished(),
+            EmitMode::Beat => {
+                let beats = beat_clock.beats();
+                let pulsed = trail.last_beat.is_some_and(|last_beat| last_beat != beats);
+                trail.last_beat = Some(beats);
+                pulsed
+            }
+            EmitMode::Manual => false,
+            EmitMode::OnTurn {
+                angle_threshold,
+                max_distance,
+            } => trail.turned(position, angle_threshold, max_distance),
+            EmitMode::Distance(spacing) => trail
+                .points
+                .back()
+                .is_none_or(|head| head.position.distance(position) >= spacing),
+        };
+        
+        let moved = trail
+            .points
+            .back()
+            .is_none_or(|head| head.position.distance(position) >= trail.min_movement);
+        
+        // Add new trail point if timer elapsed or a beat arrived
+        if should_emit && moved && position_valid && !trail.over_budget {
+            let new_point = TrailPoint {
+                position,
+                timestamp: trail.clock,
+                frame: trail.frame,
+                right: None,
+            };
+            
+            if trail.record_point(new_point) {
+                emitted_events.send(TrailPointEmitted {
+                    entity,
+                    position,
+                    timestamp: trail.clock,
+                });
+            }
+        }
+        
+        // Follow the emitter with the rendered head between emissions, while
+        // the trail emits on its own
+        let head_attached = trail.attach_head
+            && matches!(trail.style, TrailStyle::Ribbon)
+            && !matches!(trail.emit_mode, EmitMode::Manual)
+            && !trail.over_budget;
+        if position_valid && trail.emitter_position != Some(position) {
+            trail.emitter_position = Some(position);
+            if head_attached {
+                trail.dirty = true;
+            }
+        }
+        if trail.head_attached != head_attached {
+            trail.head_attached = head_attached;
+            trail.dirty = true;
+        }
+        
+        // Breathing trails oscillate how much of their retention limit they keep
+        let retention = trail
+            .breathing
+            .map_or(1.0, |breathing| breathing.retention(time.elapsed_seconds()));
+        if retention < 1.0 {
+            let limit = ((trail.max_points as f32 * retention).round() as usize).max(MIN_MAX_POINTS);
+            trail.trim_to(limit);
+        }
+        
+        // Keep the trail within its arc length budget
+        if let Some(max_length) = trail.max_length {
+            trail.trim_to_length(max_length * retention);
+        }
+        
+        trail.expire_aged(retention);
+        
+        // Over budget trails that would never expire retract from the tail at
+        // their emission rate instead
+        if trail.over_budget && trail.max_age.is_none() && trail.max_age_frames.is_none() {
+            let retracted = trail.timer.times_finished_this_tick() as usize;
+            let limit = trail.points.len().saturating_sub(retracted);
+            trail.trim_to(limit);
+        }
+    }
+}
+
+fn generate_trail_meshes(
+    time: Res<Time>,
+    budget: Res<TrailRebuildBudget>,
+    profiles: Res<Assets<TrailProfile>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut trail_query: Query<(Entity, &mut Trail), Without<Trail2d>>,
+    camera_query: Query<&GlobalTransform, With<Camera3d>>,
+    frame_query: Query<&GlobalTransform>,
+) {
+    let view = camera_query.iter().next().copied();
+    
+    let mut pending = Vec::new();
+    for (entity, mut trail) in trail_query.iter_mut() {
+        if !trail.render || trail.points.len() < trail.min_render_points() {
+            // Free the asset right away rather than leaving it to handle drops,
+            // unless the mesh entity is about to fade out with it
+            if let Some(mesh) = trail.mesh.take() {
+                if trail.fade_out.is_none() || trail.mesh_entity.is_none() {
+                    meshes.remove(&mesh);
+                }
+            }
+            continue;
+        }
+        
+        // Throttle rebuilds, but never beyond the staleness bound
+        trail.since_rebuild += time.delta_seconds();
+        trail.stale_frames += 1;
+        // A mesh removed from `Assets<Mesh>` behind our back is always rebuilt
+        let mesh_alive = trail.mesh.as_ref().is_some_and(|mesh| meshes.contains(mesh));
+        // Unchanged points give the same mesh unless it animates on its own
+        if !trail.dirty && mesh_alive && !trail.animated() {
+            continue;
+        }
+        if let (Some(hz), true) = (trail.mesh_update_hz, mesh_alive) {
+            let due = trail.since_rebuild >= 1.0 / hz;
+            let too_stale = trail
+                .max_stale_frames
+                .is_some_and(|max_stale_frames| trail.stale_frames >= max_stale_frames);
+            if !due && !too_stale {
+                continue;
+            }
+        }
+        
+        pending.push((entity, trail.priority, trail.stale_frames));
+    }
+    
+    // Over budget, rebuild the highest priority and then the stalest trails
+    if let Some(max_rebuilds) = budget.max_rebuilds_per_frame {
+        pending.sort_by_key(|&(_, priority, stale_frames)| {
+            (std::cmp::Reverse(priority), std::cmp::Reverse(stale_frames))
+        });
+        pending.truncate(max_rebuilds);
+    }
+    
+    for (entity, _, _) in pending {
+        let Ok((_, mut trail)) = trail_query.get_mut(entity) else {
+            continue;
+        };
+        let profile = trail.profile.as_ref().and_then(|profile| profiles.get(profile));
+        // Express the camera in the space the points are stored in
+        let frame = trail.frame_entity().and_then(|e| frame_query.get(e).ok());
+        let local_view = view.map(|view| match frame {
+            Some(frame) => GlobalTransform::from(frame.affine().inverse() * view.affine()),
+            None => view,
+        });
+        let mut mesh = trail.build_mesh(local_view.as_ref(), profile);
+        trail.mesh_bounds = mesh.compute_aabb();
+        // Batched trails are only drawn through their batch's merged mesh, so
+        // their own mesh never has to reach the render world
+        if trail.batched {
+            mesh.asset_usage = RenderAssetUsages::MAIN_WORLD;
+            trail.batch_dirty = true;
+        }
+        // Overwrite the existing asset in place so the mesh entity keeps its
+        // handle, only adding a new asset when the old one is gone
+        match trail.mesh.as_ref().and_then(|handle| meshes.get_mut(handle)) {
+            Some(existing) => *existing = mesh,
+            None => trail.mesh = Some(meshes.add(mesh)),
+        }
+        trail.dirty = false;
+        trail.since_rebuild = 0.0;
+        trail.stale_frames = 0;
+    }
+}
+
+/// Components of a mesh entity compared against its trail
+type MeshEntityState = (
+    &'static Handle<Mesh>,
+    Option<&'static Handle<StandardMaterial>>,
+    Option<&'static RenderLayers>,
+);
+
+/// Spawn, update and despawn trail mesh entities in a single step after all
+/// meshes for the frame have been generated, so an entity never disappears
+/// for a frame while its trail empties and refills.
+fn sync_trail_mesh_entities(
+    mut commands: Commands,
+    mut trail_query: Query<&mut Trail>,
+    mesh_entity_query: Query<MeshEntityState>,
+    mut transform_query: Query<&mut Transform, Without<Trail>>,
+    frame_query: Query<&GlobalTransform>,
+    visibility_query: Query<&Visibility>,
+    parent_query: Query<&Parent>,
+) {
+    for mut trail in trail_query.iter_mut() {
+        // Batched trails are drawn by their material's shared mesh entity
+        if trail.batched {
+            let mesh_entity = trail.mesh_entity.take();
+            if let Some(entity_commands) = mesh_entity.and_then(|e| commands.get_entity(e)) {
+                entity_commands.despawn_recursive();
+            }
+            continue;
+        }
+        
+        // Local trails parent their mesh to the emitter's parent, meshes of
+        // trails with a reference frame follow that frame around
+        let mesh_parent = match trail.space {
+            TrailSpace::Local => trail.local_parent,
+            TrailSpace::World => None,
+        };
+        let mesh_transform = match mesh_parent {
+            Some(_) => Transform::IDENTITY,
+            None => trail
+                .frame_entity()
+                .and_then(|e| frame_query.get(e).ok())
+                .map(GlobalTransform::compute_transform)
+                .unwrap_or_default(),
+        };
+        if let Some(mut transform) = trail
+            .mesh_entity
+            .and_then(|e| transform_query.get_mut(e).ok())
+        {
+            transform.set_if_neq(mesh_transform);
+        }
+        if let Some(mesh_entity) = trail.mesh_entity {
+            let current_parent = parent_query.get(mesh_entity).ok().map(Parent::get);
+            if current_parent != mesh_parent {
+                if let Some(mut entity_commands) = commands.get_entity(mesh_entity) {
+                    match mesh_parent {
+                        Some(parent) => entity_commands.set_parent(parent),
+                        None => entity_commands.remove_parent(),
+                    };
+                }
+            }
+        }
+        
+        match (trail.mesh.clone(), trail.mesh_entity) {
+            (Some(mesh), Some(mesh_entity)) => {
+                let up_to_date = mesh_entity_query
+                    .get(mesh_entity)
+                    .is_ok_and(|(current_mesh, current_material, current_layers)| {
+                        *current_mesh == mesh
+                            && (trail.custom_material || current_material == Some(&trail.material))
+                            && current_layers == trail.render_layers.as_ref()
+                    });
+                if up_to_date {
+                    continue;
+                }
+                
+                // Reuse the existing entity when it's still around
+                if let Some(mut entity_commands) = commands.get_entity(mesh_entity) {
+                    entity_commands.insert((mesh, Visibility::Inherited));
+                    if !trail.custom_material {
+                        entity_commands.insert(trail.material.clone());
+                    }
+                    match &trail.render_layers {
+                        Some(layers) => entity_commands.insert(layers.clone()),
+                        None => entity_commands.remove::<RenderLayers>(),
+                    };
+                } else {
+                    let mesh_entity = spawn_mesh_entity(&mut commands, mesh, mesh_transform, &trail);
+                    trail.mesh_entity = Some(mesh_entity);
+                }
+                trail.keep_mesh_entity = false;
+            }
+            (Some(mesh), None) => {
+                let mesh_entity = spawn_mesh_entity(&mut commands, mesh, mesh_transform, &trail);
+                trail.mesh_entity = Some(mesh_entity);
+            }
+            (None, Some(mesh_entity)) => {
+                // Reset trails park their entity until they have a mesh again
+                if trail.keep_mesh_entity {
+                    let hidden = visibility_query
+                        .get(mesh_entity)
+                        .is_ok_and(|visibility| *visibility == Visibility::Hidden);
+                    if !hidden {
+                        if let Some(mut entity_commands) = commands.get_entity(mesh_entity) {
+                            entity_commands.insert(Visibility::Hidden);
+                        }
+                    }
+                    continue;
+                }
+                // Recursive despawn also detaches parented meshes of local trails
+                if let Some(mut entity_commands) = commands.get_entity(mesh_entity) {
+                    match trail.fade_out {
+                        Some(duration) => {
+                            entity_commands.try_insert(TrailFadeOut::new(duration));
+                        }
+                        None => entity_commands.despawn_recursive(),
+                    }
+                }
+                trail.mesh_entity = None;
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+/// Refresh the bounding boxes of rebuilt trail meshes, which Bevy only
+/// computes once when the mesh entity spawns
+fn update_trail_mesh_bounds(mut commands: Commands, mut trail_query: Query<&mut Trail>) {
+    for mut trail in trail_query.iter_mut() {
+        if trail.mesh_bounds.is_none() {
+            continue;
+        }
+        let bounds = trail.mesh_bounds.take();
+        let mesh_entity = trail.mesh_entity.and_then(|e| commands.get_entity(e));
+        let Some(mut entity_commands) = mesh_entity else {
+            continue;
+        };
+        match (trail.frustum_culling, bounds) {
+            (true, Some(aabb)) => {
+                entity_commands.insert(aabb).remove::<NoFrustumCulling>();
+            }
+            _ => {
+                entity_commands.insert(NoFrustumCulling);
+            }
+        }
+    }
+}
+
+/// Make the materials of `double_sided` trails render both faces
+fn apply_double_sided_materials(
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    trail_query: Query<&Trail>,
+    mut warned_custom_material: Local<bool>,
+) {
+    for trail in trail_query.iter() {
+        if !trail.double_sided {
+            continue;
+        }
+        // Custom materials decide their own culling
+        if trail.custom_material {
+            if !*warned_custom_material {
+                warn!("Trail double_sided has no effect while a TrailMaterial draws the trail");
+                *warned_custom_material = true;
+            }
+            continue;
+        }
+        // Only touch materials that still need it to avoid change events
+        let needs_update = materials
+            .get(&trail.material)
+            .is_some_and(|material| !material.double_sided || material.cull_mode.is_some());
+        if let (true, Some(material)) = (needs_update, materials.get_mut(&trail.material)) {
+            material.double_sided = true;
+            material.cull_mode = None;
+        }
+    }
+}
+
+/// Despawn a trail's mesh entity along with its owner or the removed `Trail`
+fn despawn_removed_trail_meshes(
+    trigger: Trigger<OnRemove, Trail>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    trail_query: Query<&Trail>,
+) {
+    let Ok(trail) = trail_query.get(trigger.entity()) else {
+        return;
+    };
+    if let Some(mut entity_commands) = trail.mesh_entity.and_then(|e| commands.get_entity(e)) {
+        // A fading mesh entity frees its mesh itself when the fade ends. It
+        // may be despawned along with its owner before the insert applies.
+        if let Some(duration) = trail.fade_out {
+            entity_commands.try_insert(TrailFadeOut::new(duration));
+            return;
+        }
+        entity_commands.despawn_recursive();
+    }
+    if let Some(mesh) = &trail.mesh {
+        meshes.remove(mesh);
+    }
+}
+
+fn spawn_mesh_entity(
+    commands: &mut Commands,
+    mesh: Handle<Mesh>,
+    transform: Transform,
+    trail: &Trail,
+) -> Entity {
+    let mut entity_commands = commands.spawn(PbrBundle {
+        mesh,
+        material: trail.material.clone(),
+        transform,
+        ..default()
+    });
+    if let Some(layers) = &trail.render_layers {
+        entity_commands.insert(layers.clone());
+    }
+    if trail.custom_material {
+        entity_commands.remove::<Handle<StandardMaterial>>();
+    }
+    if !trail.frustum_culling {
+        entity_commands.insert(NoFrustumCulling);
+    }
+    if let (TrailSpace::Local, Some(parent)) = (trail.space, trail.local_parent) {
+        entity_commands.set_parent(parent);
+    }
+    entity_commands.id()
+}
+
+/// Create an image that a camera can render trails into for compositing
+pub fn trail_render_target(width: u32, height: u32) -> Image {
+    let size = Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+    };
+    let mut image = Image::new_fill(
+        size,
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Bgra8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
+        | TextureUsages::COPY_DST
+        | TextureUsages::RENDER_ATTACHMENT;
+    image
+}
+
+/// Create a white radial-gradient image whose alpha falls off smoothly to the
+/// edge, the default texture for [`TrailStyle::SoftPuffs`]
+pub fn soft_puff_image(size: u32) -> Image {
+    let size = size.max(1);
+    let center = size as f32 * 0.5;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let offset = Vec2::new(x as f32 + 0.5, y as f32 + 0.5) - center;
+            let distance = (offset.length() / center).min(1.0);
+            // Smoothstep falloff so overlapping puffs show no hard rims
+            let falloff = 1.0 - distance * distance * (3.0 - 2.0 * distance);
+            data.extend([255, 255, 255, (falloff * 255.0).round() as u8]);
+        }
+    }
+    
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+/// Insert `subdivisions` points between each pair along a Catmull-Rom spline
+/// through the points, interpolating timestamps linearly
+fn catmull_rom_points(points: &VecDeque<TrailPoint>, subdivisions: usize) -> VecDeque<TrailPoint> {
+    if points.len() < 2 {
+        return points.clone();
+    }
+    
+    let last = points.len() - 1;
+    let mut smoothed = VecDeque::with_capacity(last * (subdivisions + 1) + 1);
+    for i in 0..last {
+        // Mirror the end points to get tangents at both ends of the trail
+        let p1 = points[i].position;
+        let p2 = points[i + 1].position;
+        let p0 = if i > 0 { points[i - 1].position } else { 2.0 * p1 - p2 };
+        let p3 = if i + 1 < last { points[i + 2].position } else { 2.0 * p2 - p1 };
+        
+        smoothed.push_back(points[i].clone());
+        for step in 1..=subdivisions {
+            let t = step as f32 / (subdivisions + 1) as f32;
+            let t2 = t * t;
+            let t3 = t2 * t;
+            let position = 0.5
+                * (2.0 * p1
+                    + (p2 - p0) * t
+                    + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+                    + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3);
+            let (a, b) = (&points[i], &points[i + 1]);
+            smoothed.push_back(TrailPoint {
+                position,
+                timestamp: a.timestamp + (b.timestamp - a.timestamp) * t,
+                frame: a.frame,
+                right: None,
+            });
+        }
+    }
+    smoothed.push_back(points[last].clone());
+    
+    smoothed
+}
+
+/// Ramer-Douglas-Peucker simplification, keeping both end points
+fn simplify_points(points: &VecDeque<TrailPoint>, tolerance: f32) -> VecDeque<TrailPoint> {
+    if points.len() < 3 {
+        return points.clone();
+    }
+    
+    let last = points.len() - 1;
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[last] = true;
+    
+    let mut stack = vec![(0, last)];
+    while let Some((start, end)) = stack.pop() {
+        let a = points[start].position;
+        let b = points[end].position;
+        
+        // Find the point furthest from the chord between start and end
+        let mut max_distance = 0.0;
+        let mut index = start;
+        for (i, point) in points.iter().enumerate().take(end).skip(start + 1) {
+            let distance = distance_to_segment(point.position, a, b);
+            if distance > max_distance {
+                max_distance = distance;
+                index = i;
+            }
+        }
+        
+        if max_distance > tolerance {
+            keep[index] = true;
+            stack.push((start, index));
+            stack.push((index, end));
+        }
+    }
+    
+    points
+        .iter()
+        .zip(keep)
+        .filter(|(_, keep)| *keep)
+        .map(|(point, _)| point.clone())
+        .collect()
+}
+
+fn distance_to_segment(p: Vec3, a: Vec3, b: Vec3) -> f32 {
+    let ab = b - a;
+    let length_squared = ab.length_squared();
+    let t = if length_squared > 0.0 {
+        ((p - a).dot(ab) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    p.distance(a + ab * t)
+}
+
+/// Cross-section of the ribbon at one point along the trail
+#[derive(Clone, Copy)]
+struct RibbonFrame {
+    position: Vec3,
+    forward: Vec3,
+    right: Vec3,
+    left_width: f32,
+    right_width: f32,
+    progress: f32,
+    color: Option<LinearRgba>,
+}
+
+impl RibbonFrame {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            position: self.position.lerp(other.position, t),
+            forward: self.forward.lerp(other.forward, t).try_normalize().unwrap_or(self.forward),
+            right: self.right.lerp(other.right, t).try_normalize().unwrap_or(self.right),
+            left_width: self.left_width + (other.left_width - self.left_width) * t,
+            right_width: self.right_width + (other.right_width - self.right_width) * t,
+            progress: self.progress + (other.progress - self.progress) * t,
+            color: self.color.zip(other.color).map(|(a, b)| a.mix(&b, t)),
+        }
+    }
+}
+
+/// Alignment with the up axis where the fallback axis starts blending in
+const FALLBACK_BLEND_START: f32 = 0.85;
+/// Alignment with the up axis where only the fallback axis is used
+const FALLBACK_BLEND_END: f32 = 0.95;
+
+/// Right vector for a ribbon travelling along `dir`.
+///
+/// Crossing with `up` degenerates as `dir` lines up with it, so around a 0.9
+/// alignment the result blends smoothly towards crossing with `fallback`
+/// instead of snapping between the two.
+fn ribbon_right(dir: Vec3, up: Vec3, fallback: Vec3) -> Vec3 {
+    let primary = dir.cross(up).normalize_or_zero();
+    let mut secondary = dir.cross(fallback).normalize_or_zero();
+    // Keep both candidates on the same side so the blend never passes zero
+    if secondary.dot(primary) < 0.0 {
+        secondary = -secondary;
+    }
+    
+    let alignment = dir.dot(up.normalize_or_zero()).abs();
+    let t = ((alignment - FALLBACK_BLEND_START) / (FALLBACK_BLEND_END - FALLBACK_BLEND_START))
+        .clamp(0.0, 1.0);
+    let blend = t * t * (3.0 - 2.0 * t);
+    primary.lerp(secondary, blend).try_normalize().unwrap_or(secondary)
+}
+
+/// Radius of the circle through three consecutive points, and whether its
+/// center lies on the `right` side of the path. `None` for straight runs.
+fn turn_radius(prev: Vec3, point: Vec3, next: Vec3, right: Vec3) -> Option<(f32, bool)> {
+    let incoming = point - prev;
+    let outgoing = next - point;
+    let bend = incoming.cross(outgoing).length();
+    if bend <= f32::EPSILON {
+        return None;
+    }
+    let radius = incoming.length() * outgoing.length() * (next - prev).length() / (2.0 * bend);
+    // The path turns towards the center of curvature
+    let turn = outgoing.normalize_or_zero() - incoming.normalize_or_zero();
+    Some((radius, turn.dot(right) > 0.0))
+}
+
+/// Vertex color at a ribbon point, `None` when no profile, gradient or fade
+/// colors the ribbon
+fn ribbon_color(
+    trail: &Trail,
+    profile: Option<&TrailProfile>,
+    progress: f32,
+    timestamp: f32,
+) -> Option<LinearRgba> {
+    let profiled = profile.and_then(|profile| profile.color_at(progress));
+    let gradient = trail
+        .color_gradient
+        .map(|(tail, head)| tail.to_linear().mix(&head.to_linear(), progress));
+    let color = match (profiled, gradient) {
+        (Some(a), Some(b)) => Some(LinearRgba::from_vec4(a.to_vec4() * b.to_vec4())),
+        (a, b) => a.or(b),
+    };
+    if !trail.fades() {
+        return color;
+    }
+    let color = color.unwrap_or(LinearRgba::WHITE);
+    let fade = trail.fade_alpha(trail.clock - timestamp);
+    Some(color.with_alpha(color.alpha * fade))
+}
+
+fn ribbon_frames(
+    points: &VecDeque<TrailPoint>,
+    trail: &Trail,
+    profile: Option<&TrailProfile>,
+    camera: Option<Vec3>,
+) -> Vec<RibbonFrame> {
+    let mut frames: Vec<RibbonFrame> = Vec::with_capacity(points.len());
+    let mut distance = 0.0;
+    // Untwisted right vector of the previous frame, for parallel transport
+    let mut previous_right: Option<Vec3> = None;
+    for (i, point) in points.iter().enumerate() {
+        let progress = i as f32 / (points.len() - 1) as f32;
+        if i > 0 {
+            distance += points[i - 1].position.distance(point.position);
+        }
+        
+        // Calculate direction vector
+        let (forward, right) = if i == 0 {
+            // First point - use direction to next point
+            let next = &points[i + 1];
+            let dir = (next.position - point.position).normalize_or_zero();
+            (dir, ribbon_right(dir, trail.up_axis, trail.fallback_axis))
+        } else if i == points.len() - 1 {
+            // Last point - use direction from previous point
+            let prev = &points[i - 1];
+            let dir = (point.position - prev.position).normalize_or_zero();
+            (dir, ribbon_right(dir, trail.up_axis, trail.fallback_axis))
+        } else {
+            // Middle point - average of directions
+            let prev = &points[i - 1];
+            let next = &points[i + 1];
+            let dir = ((point.position - prev.position) + (next.position - point.position))
+                .normalize_or_zero();
+            (dir, ribbon_right(dir, trail.up_axis, trail.fallback_axis))
+        };
+        
+        // Coincident points give no direction, so keep the previous frame's
+        // orientation, or the first one the trail has at all
+        let (forward, right) = if right.length_squared() > f32::EPSILON {
+            (forward, right)
+        } else if let Some(previous) = frames.last() {
+            (previous.forward, previous.right)
+        } else {
+            let forward = points
+                .iter()
+                .zip(points.iter().skip(1))
+                .find_map(|(a, b)| (b.position - a.position).try_normalize())
+                .unwrap_or(Vec3::NEG_Z);
+            let right = ribbon_right(forward, trail.up_axis, trail.fallback_axis);
+            (forward, right.try_normalize().unwrap_or(Vec3::X))
+        };
+        
+        // Billboarded ribbons span perpendicular to the view direction
+        let right = match (trail.face_camera, camera) {
+            (true, Some(camera)) => forward
+                .cross(camera - point.position)
+                .try_normalize()
+                .unwrap_or(right),
+            _ => right,
+        };
+        
+        // Flat ribbons travel along the direction projected into their plane,
+        // keeping the previous orientation while moving along the axis
+        let (forward, right) = match trail.orient {
+            OrientMode::Adaptive => (forward, right),
+            OrientMode::FixedAxis(axis) => {
+                let axis = axis.try_normalize().unwrap_or(Vec3::Y);
+                match forward.reject_from_normalized(axis).try_normalize() {
+                    Some(flat) => (flat, flat.cross(axis)),
+                    None => match frames.last() {
+                        Some(frame) => (frame.forward, frame.right),
+                        None => {
+                            let flat = axis.any_orthonormal_vector();
+                            (flat, flat.cross(axis))
+                        }
+                    },
+                }
+            }
+            // Stored points carry the right vector transported to them on
+            // emission, so the ribbon keeps its roll as the tail expires.
+            // Points generated in between are transported from the previous
+            // frame.
+            OrientMode::ParallelTransport => {
+                let carried = point.right.or(previous_right).unwrap_or(right);
+                let transported = carried.reject_from_normalized(forward).try_normalize();
+                (forward, transported.unwrap_or(right))
+            }
+        };
+        previous_right = Some(right);
+        
+        // Roll about the direction of travel by the distance along the ribbon,
+        // which keeps the twist continuous through the end points
+        let right = if trail.twist != 0.0 {
+            Quat::from_axis_angle(forward, trail.twist * distance) * right
+        } else {
+            right
+        };
+        
+        let (mut left_edge, mut right_edge) = trail.edge_widths(progress, profile);
+        
+        // Keep the inner edge inside the turn radius so it can't invert
+        if trail.clamp_to_curvature && i > 0 && i < points.len() - 1 {
+            let (prev, next) = (points[i - 1].position, points[i + 1].position);
+            if let Some((radius, inner_is_right)) = turn_radius(prev, point.position, next, right) {
+                if inner_is_right {
+                    right_edge = right_edge.min(radius);
+                } else {
+                    left_edge = left_edge.min(radius);
+                }
+            }
+        }
+        
+        frames.push(RibbonFrame {
+            position: point.position,
+            forward,
+            right,
+            left_width: left_edge,
+            right_width: right_edge,
+            progress,
+            color: ribbon_color(trail, profile, progress, point.timestamp),
+        });
+    }
+    
+    frames
+}
+
+fn create_trail_mesh(
+    points: &VecDeque<TrailPoint>,
+    trail: &Trail,
+    profile: Option<&TrailProfile>,
+    camera: Option<Vec3>,
+) -> Mesh {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut edges = Vec::new();
+    let mut colors = Vec::new();
+    
+    if points.len() < 2 {
+        return Mesh::new(PrimitiveTopology::TriangleList, default());
+    }
+    
+    let frames = ribbon_frames(points, trail, profile, camera);
+    
+    // Subdivide each segment into evenly spaced rows
+    let along = trail.tessellation.0.max(1);
+    let across = trail.tessellation.1.max(1);
+    let mut rows = Vec::with_capacity((frames.len() - 1) * along + 1);
+    for pair in frames.windows(2) {
+        for step in 0..along {
+            rows.push(pair[0].lerp(&pair[1], step as f32 / along as f32));
+        }
+    }
+    rows.extend(frames.last().copied());
+    
+    // Scrolling V offset makes the texture flow towards the tail
+    let uv_offset = trail.clock * trail.uv_scroll_speed;
+    
+    // Generate a row of vertices from the left to the right edge
+    let mut last_normal = trail.up_axis.try_normalize().unwrap_or(Vec3::Y);
+    for row in &rows {
+        let left_pos = row.position - row.right * row.left_width;
+        let right_pos = row.position + row.right * row.right_width;
+        
+        // Surface normal of the ribbon. Frames already average the direction of
+        // neighbouring segments, so rows shared by two segments get a smooth
+        // normal; degenerate rows reuse the previous one.
+        let normal = row.right.cross(row.forward).try_normalize().unwrap_or(last_normal);
+        last_normal = normal;
+        
+        for column in 0..=across {
+            let u = column as f32 / across as f32;
+            let position = left_pos.lerp(right_pos, u);
+            vertices.push([position.x, position.y, position.z]);
+            
+            normals.push(normal.to_array());
+            
+            // Add UVs
+            uvs.push([u, row.progress * trail.uv_tiling + uv_offset]);
+            
+            // Centered edge coordinate plus half-width for shader-side feathering
+            let side = u * 2.0 - 1.0;
+            let side_width = if side <= 0.0 { row.left_width } else { row.right_width };
+            edges.push([side, side_width]);
+            
+            if let Some(color) = row.color {
+                colors.push(color.to_f32_array());
+            }
+        }
+    }
+    
+    // Rows alternate left and right vertices, which already form a strip.
+    // Odd strip triangles have their winding flipped by the rasterizer, so
+    // they face the same way as the list's triangles.
+    let strip =
+        trail.triangle_strip && across == 1 && trail.cap == CapStyle::None && !trail.batched;
+    
+    // Otherwise generate two triangles per grid cell
+    if !strip {
+        let columns = (across + 1) as u32;
+        for row in 0..(rows.len() - 1) as u32 {
+            for column in 0..across as u32 {
+                let base = row * columns + column;
+                indices.extend([base, base + 1, base + columns]);
+                indices.extend([base + 1, base + columns + 1, base + columns]);
+            }
+        }
+    }
+    
+    // Fan a cap out from the middle of the first and last rows
+    let end_rows = rows.first().zip(rows.last());
+    if let (true, Some((first, last))) = (trail.cap != CapStyle::None, end_rows) {
+        for (row, outward) in [(first, -first.forward), (last, last.forward)] {
+            let normal = row.right.cross(row.forward).try_normalize().unwrap_or(last_normal);
+            let v = row.progress * trail.uv_tiling + uv_offset;
+            let total = row.left_width + row.right_width;
+            let left_pos = row.position - row.right * row.left_width;
+            let right_pos = row.position + row.right * row.right_width;
+            let center = left_pos.lerp(right_pos, 0.5);
+            let outline = trail.cap.outline(left_pos, right_pos, outward, total * 0.5);
+            
+            let base = vertices.len() as u32;
+            for position in std::iter::once(center).chain(outline.iter().copied()) {
+                let lateral = (position - row.position).dot(row.right);
+                let u = if total > 0.0 {
+                    ((lateral + row.left_width) / total).clamp(0.0, 1.0)
+                } else {
+                    0.5
+                };
+                vertices.push(position.to_array());
+                normals.push(normal.to_array());
+                uvs.push([u, v]);
+                let side = u * 2.0 - 1.0;
+                let side_width = if side <= 0.0 { row.left_width } else { row.right_width };
+                edges.push([side, side_width]);
+                if let Some(color) = row.color {
+                    colors.push(color.to_f32_array());
+                }
+            }
+            
+            // Wind the fan the same way as the ribbon so it faces along `normal`
+            for (i, pair) in outline.windows(2).enumerate() {
+                let (a, b) = (base + i as u32 + 1, base + i as u32 + 2);
+                if (pair[0] - center).cross(pair[1] - center).dot(normal) >= 0.0 {
+                    indices.extend([base, a, b]);
+                } else {
+                    indices.extend([base, b, a]);
+                }
+            }
+        }
+    }
+    
+    let topology = if strip {
+        PrimitiveTopology::TriangleStrip
+    } else {
+        PrimitiveTopology::TriangleList
+    };
+    let mut mesh = Mesh::new(topology, default());
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    if trail.edge_attribute {
+        mesh.insert_attribute(ATTRIBUTE_EDGE_DISTANCE, edges);
+    }
+    if !colors.is_empty() {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    }
+    if !strip {
+        mesh.insert_indices(Indices::U32(indices));
+    }
+    
+    mesh
+}
+
+/// Build a flat ribbon in the XY plane, with the same edge widths as the 3D
+/// ribbon of `trail`
+fn create_trail_mesh_2d(points: &[Vec2], trail: &Trail) -> Mesh {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    
+    if points.len() < 2 {
+        return Mesh::new(PrimitiveTopology::TriangleList, default());
+    }
+    
+    let last = points.len() - 1;
+    
+    for (i, point) in points.iter().enumerate() {
+        let progress = i as f32 / last as f32;
+        
+        // Direction through the neighbouring points, perpendicular in-plane
+        let prev = points[i.saturating_sub(1)];
+        let next = points[(i + 1).min(last)];
+        let dir = (next - prev).normalize_or_zero();
+        let right = Vec2::new(dir.y, -dir.x);
+        
+        let (left_width, right_width) = trail.edge_widths(progress, None);
+        let left_pos = *point - right * left_width;
+        let right_pos = *point + right * right_width;
+        
+        vertices.push([left_pos.x, left_pos.y, 0.0]);
+        vertices.push([right_pos.x, right_pos.y, 0.0]);
+        normals.push([0.0, 0.0, 1.0]);
+        normals.push([0.0, 0.0, 1.0]);
+        uvs.push([0.0, progress]);
+        uvs.push([1.0, progress]);
+    }
+    
+    for i in 0..last {
+        let base = (i * 2) as u32;
+        indices.extend([base, base + 1, base + 2, base + 1, base + 3, base + 2]);
+    }
+    
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, default());
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.insert_indices(Indices::U32(indices));
+    
+    mesh
+}
+
+/// Build a camera-facing quad per point, sized and faded by `appearance`,
+/// which maps a point's age to its scale and alpha
+fn create_quad_mesh(
+    points: &VecDeque<TrailPoint>,
+    width: f32,
+    current_time: f32,
+    right: Vec3,
+    up: Vec3,
+    appearance: impl Fn(f32) -> (f32, f32),
+) -> Mesh {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut colors = Vec::new();
+    
+    let normal = right.cross(up).normalize_or_zero();
+    
+    for point in points {
+        // Resize and fade each quad as its point ages
+        let age = current_time - point.timestamp;
+        let (scale, alpha) = appearance(age);
+        if scale <= 0.0 || alpha <= 0.0 {
+            continue;
+        }
+        
+        let half_size = width * 0.5 * scale;
+        let right = right * half_size;
+        let up = up * half_size;
+        let base = vertices.len() as u32;
+        
+        for corner in [
+            point.position - right - up,
+            point.position + right - up,
+            point.position + right + up,
+            point.position - right + up,
+        ] {
+            vertices.push([corner.x, corner.y, corner.z]);
+            normals.push([normal.x, normal.y, normal.z]);
+            colors.push([1.0, 1.0, 1.0, alpha]);
+        }
+        uvs.extend([[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]]);
+        indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+    }
+    
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, default());
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh.insert_indices(Indices::U32(indices));
+    
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+    
+    use bevy::ecs::schedule::ExecutorKind;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::math::Vec3A;
+    use bevy::log::tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+    use bevy::log::tracing_subscriber::Registry;
+    use bevy::render::mesh::VertexAttributeValues;
+    use bevy::sprite::Mesh2dHandle;
+    use bevy::time::TimeUpdateStrategy;
+    use bevy::utils::tracing::{self, Event, Level, Subscriber};
+    
+    use super::*;
+    
+    /// Headless app running the plugin on a fixed 60 Hz timestep
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), TrailPlugin))
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .init_asset::<Image>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                1.0 / 60.0,
+            )))
+            .edit_schedule(Update, |schedule| {
+                schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+            });
+        app
+    }
+    
+    /// Ribbon emitting on every frame at a constant width
+    fn test_trail() -> Trail {
+        let mut trail = Trail::new(32, 120.0, 1.0, Handle::default());
+        trail.taper = TaperMode::None;
+        trail
+    }
+    
+    fn spawn_trail(app: &mut App, trail: Trail, position: Vec3) -> Entity {
+        app.world_mut()
+            .spawn((trail, TransformBundle::from_transform(Transform::from_translation(position))))
+            .id()
+    }
+    
+    /// Move the emitter and run a frame
+    fn step(app: &mut App, entity: Entity, position: Vec3) {
+        app.world_mut().get_mut::<Transform>(entity).unwrap().translation = position;
+        app.update();
+    }
+    
+    fn trail(app: &App, entity: Entity) -> &Trail {
+        app.world().get::<Trail>(entity).unwrap()
+    }
+    
+    fn trail_mut(app: &mut App, entity: Entity) -> Mut<'_, Trail> {
+        app.world_mut().get_mut::<Trail>(entity).unwrap()
+    }
+    
+    /// Counts warnings logged by this crate
+    struct CountWarnings(Arc<AtomicUsize>);
+    
+    impl<S: Subscriber> Layer<S> for CountWarnings {
+        fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+            let metadata = event.metadata();
+            if *metadata.level() == Level::WARN && metadata.target().starts_with("bevy_trail") {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+    
+    /// Run `f` on this thread and return how many warnings it logged
+    fn count_warnings(f: impl FnOnce()) -> usize {
+        let count = Arc::new(AtomicUsize::new(0));
+        let subscriber = Registry::default().with(CountWarnings(count.clone()));
+        tracing::subscriber::with_default(subscriber, f);
+        count.load(Ordering::Relaxed)
+    }
+    
+    /// Number of entities drawing a 3D or 2D mesh
+    fn mesh_entity_count(app: &mut App) -> usize {
+        app.world_mut()
+            .query_filtered::<(), Or<(With<Handle<Mesh>>, With<Mesh2dHandle>)>>()
+            .iter(app.world())
+            .count()
+    }
+    
+    fn mesh_positions(mesh: &Mesh) -> Vec<Vec3> {
+        match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+            Some(VertexAttributeValues::Float32x3(positions)) => {
+                positions.iter().copied().map(Vec3::from).collect()
+            }
+            _ => panic!("trail mesh has no positions"),
+        }
+    }
+    
+    fn assert_encloses(aabb: &Aabb, mesh: &Mesh) {
+        let (min, max) = (aabb.min(), aabb.max());
+        for position in mesh_positions(mesh) {
+            let position = Vec3A::from(position);
+            assert!(
+                position.cmpge(min - 1e-4).all() && position.cmple(max + 1e-4).all(),
+                "vertex {position} is outside {aabb:?}"
+            );
+        }
+    }
+    
+    fn trail_mesh(app: &App, entity: Entity) -> &Mesh {
+        let handle = trail(app, entity).mesh.as_ref().expect("trail has no mesh");
+        app.world().resource::<Assets<Mesh>>().get(handle).expect("trail mesh was removed")
+    }
+    
+    #[test]
+    fn set_width_regenerates_mesh() {
+        let mut app = app();
+        let entity = spawn_trail(&mut app, test_trail(), Vec3::ZERO);
+        for i in 1..=4 {
+            step(&mut app, entity, Vec3::X * i as f32);
+        }
+        
+        trail_mut(&mut app, entity).set_width(3.0);
+        app.update();
+        
+        let positions = mesh_positions(trail_mesh(&app, entity));
+        assert!(!positions.is_empty());
+        for position in positions {
+            let half_width = position.yz().length();
+            assert!((half_width - 1.5).abs() < 1e-4, "vertex {position} isn't 1.5 off the path");
+        }
+    }
+    
+    #[test]
+    fn max_points_clamped_with_one_warning() {
+        let mut app = app();
+        let entities = [0, 1].map(|max_points| {
+            let mut trail = test_trail();
+            trail.max_points = max_points;
+            spawn_trail(&mut app, trail, Vec3::ZERO)
+        });
+        
+        let warnings = count_warnings(|| {
+            for i in 1..=4 {
+                for entity in entities {
+                    app.world_mut().get_mut::<Transform>(entity).unwrap().translation =
+                        Vec3::X * i as f32;
+                }
+                app.update();
+            }
+        });
+        
+        assert_eq!(warnings, 1);
+        for entity in entities {
+            assert_eq!(trail(&app, entity).max_points, MIN_MAX_POINTS);
+            assert_eq!(trail(&app, entity).point_count(), MIN_MAX_POINTS);
+        }
+    }
+    
+    #[test]
+    fn removed_mesh_asset_is_recreated() {
+        let mut app = app();
+        let entity = spawn_trail(&mut app, test_trail(), Vec3::ZERO);
+        for i in 1..=4 {
+            step(&mut app, entity, Vec3::X * i as f32);
+        }
+        
+        let handle = trail(&app, entity).mesh.clone().unwrap();
+        let mesh_entity = trail(&app, entity).mesh_entity().unwrap();
+        app.world_mut().resource_mut::<Assets<Mesh>>().remove(&handle);
+        app.update();
+        
+        let trail = trail(&app, entity);
+        let mesh = trail.mesh.as_ref().unwrap();
+        assert!(app.world().resource::<Assets<Mesh>>().contains(mesh));
+        assert_eq!(trail.mesh_entity(), Some(mesh_entity));
+        assert_eq!(app.world().get::<Handle<Mesh>>(mesh_entity), Some(mesh));
+    }
+    
+    #[test]
+    fn invalid_emit_rate_is_ignored() {
+        let mut trail = test_trail();
+        for emit_rate in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            trail.set_emit_rate(emit_rate);
+        }
+        assert_eq!(trail.emit_rate, 120.0);
+        
+        let default_rate = Trail::builder().build().emit_rate;
+        assert_eq!(Trail::builder().emit_rate(0.0).build().emit_rate, default_rate);
+    }
+    
+    #[test]
+    fn stationary_emitter_merges_points() {
+        let mut app = app();
+        let entity = spawn_trail(&mut app, test_trail(), Vec3::ZERO);
+        for i in 1..=3 {
+            step(&mut app, entity, Vec3::X * i as f32);
+        }
+        let point_count = trail(&app, entity).point_count();
+        
+        for _ in 0..10 {
+            step(&mut app, entity, Vec3::X * 3.0);
+        }
+        
+        let trail = trail(&app, entity);
+        assert_eq!(trail.point_count(), point_count);
+        assert_eq!(trail.points.back().unwrap().timestamp, trail.clock());
+    }
+    
+    #[test]
+    fn reset_leaves_no_segment_to_old_location() {
+        let mut app = app();
+        let entity = spawn_trail(&mut app, test_trail(), Vec3::ZERO);
+        for i in 1..=4 {
+            step(&mut app, entity, Vec3::X * i as f32);
+        }
+        let mesh_entity = trail(&app, entity).mesh_entity().unwrap();
+        
+        trail_mut(&mut app, entity).reset();
+        let respawn = Vec3::new(0.0, 0.0, 100.0);
+        for i in 0..=4 {
+            step(&mut app, entity, respawn + Vec3::X * i as f32);
+        }
+        
+        assert_eq!(trail(&app, entity).mesh_entity(), Some(mesh_entity));
+        for position in mesh_positions(trail_mesh(&app, entity)) {
+            assert!(position.z > 99.0, "vertex {position} reaches back to the old location");
+        }
+    }
+    
+    #[test]
+    fn over_budget_trail_without_max_age_retracts() {
+        let mut app = app();
+        let entities = [1, 0].map(|priority| {
+            let mut trail = test_trail().with_max_age(None);
+            trail.priority = priority;
+            spawn_trail(&mut app, trail, Vec3::ZERO)
+        });
+        let move_all = |app: &mut App, position: Vec3| {
+            for entity in entities {
+                app.world_mut().get_mut::<Transform>(entity).unwrap().translation = position;
+            }
+            app.update();
+        };
+        for i in 1..=5 {
+            move_all(&mut app, Vec3::X * i as f32);
+        }
+        
+        app.world_mut().resource_mut::<TrailBudget>().max_active = 1;
+        for i in 6..=20 {
+            move_all(&mut app, Vec3::X * i as f32);
+        }
+        
+        let [active, inactive] = entities;
+        assert!(!trail(&app, active).is_over_budget());
+        assert!(trail(&app, active).point_count() > 5);
+        assert!(trail(&app, inactive).is_over_budget());
+        assert_eq!(trail(&app, inactive).point_count(), 0);
+    }
+    
+    #[test]
+    fn nan_transform_never_reaches_mesh() {
+        let mut app = app();
+        let entity = spawn_trail(&mut app, test_trail(), Vec3::ZERO);
+        for i in 1..=4 {
+            step(&mut app, entity, Vec3::X * i as f32);
+        }
+        for _ in 0..4 {
+            step(&mut app, entity, Vec3::NAN);
+            let mesh = trail_mesh(&app, entity);
+            for (_, values) in mesh.attributes() {
+                if let VertexAttributeValues::Float32x3(values) = values {
+                    assert!(values.iter().flatten().all(|value| value.is_finite()));
+                }
+            }
+            assert!(trail(&app, entity).positions().all(Vec3::is_finite));
+        }
+        
+        step(&mut app, entity, Vec3::X * 5.0);
+        assert!(mesh_positions(trail_mesh(&app, entity)).iter().all(|p| p.is_finite()));
+    }
+    
+    #[test]
+    fn despawned_particle_and_ui_trails_free_their_meshes() {
+        let mut app = app();
+        app.world_mut().spawn((Window::default(), bevy::window::PrimaryWindow));
+        let particles = app.world_mut().spawn(ParticleTrails::new(test_trail())).id();
+        let ui = app.world_mut().spawn(UiTrail::new(32, 120.0, 4.0, Handle::default())).id();
+        for i in 1..=4 {
+            let offset = i as f32;
+            app.world_mut()
+                .get_mut::<ParticleTrails>(particles)
+                .unwrap()
+                .set_positions(&[Vec3::X * offset, Vec3::Y * offset]);
+            app.world_mut().get_mut::<UiTrail>(ui).unwrap().position = Vec2::splat(offset * 10.0);
+            app.update();
+        }
+        assert_eq!(mesh_entity_count(&mut app), 2);
+        
+        app.world_mut().despawn(particles);
+        app.world_mut().despawn(ui);
+        app.update();
+        
+        assert_eq!(mesh_entity_count(&mut app), 0);
+        assert!(app.world().resource::<Assets<Mesh>>().is_empty());
+    }
+    
+    #[test]
+    fn ui_trail_bounds_follow_the_emitter() {
+        let mut app = app();
+        app.world_mut().spawn((Window::default(), bevy::window::PrimaryWindow));
+        let ui = app.world_mut().spawn(UiTrail::new(8, 120.0, 4.0, Handle::default())).id();
+        for i in 1..=40 {
+            app.world_mut().get_mut::<UiTrail>(ui).unwrap().position = Vec2::X * i as f32 * 50.0;
+            app.update();
+        }
+        
+        // The emitter ends far outside the mesh the trail spawned with
+        let (handle, aabb) = app
+            .world_mut()
+            .query::<(&Mesh2dHandle, &Aabb)>()
+            .single(app.world());
+        let mesh = app.world().resource::<Assets<Mesh>>().get(&handle.0).unwrap();
+        assert!(aabb.min().x > 1000.0);
+        assert_encloses(aabb, mesh);
+    }
+    
+    #[test]
+    fn trail_2d_uses_edge_widths_and_follows_bounds() {
+        let mut app = app();
+        app.init_asset::<ColorMaterial>();
+        let mut lopsided = test_trail();
+        lopsided.left_width = Some(1.0);
+        lopsided.right_width = Some(0.25);
+        let entity = app
+            .world_mut()
+            .spawn((lopsided, Trail2d::new(Handle::default()), TransformBundle::default()))
+            .id();
+        for i in 1..=40 {
+            step(&mut app, entity, Vec3::X * i as f32);
+        }
+        
+        let (handle, aabb) = app
+            .world_mut()
+            .query::<(&Mesh2dHandle, &Aabb)>()
+            .single(app.world());
+        let mesh = app.world().resource::<Assets<Mesh>>().get(&handle.0).unwrap();
+        assert_encloses(aabb, mesh);
+        for edge in mesh_positions(mesh).chunks(2) {
+            assert!((edge[0].y - 1.0).abs() < 1e-4, "left edge at {}", edge[0]);
+            assert!((edge[1].y + 0.25).abs() < 1e-4, "right edge at {}", edge[1]);
+        }
+    }
+    
+    #[test]
+    fn rider_keeps_up_with_a_moving_trail() {
+        let mut app = app();
+        app.add_plugins((TransformPlugin, HierarchyPlugin));
+        let entity = spawn_trail(&mut app, test_trail(), Vec3::ZERO);
+        let rider = app
+            .world_mut()
+            .spawn((TrailRider { t: 0.5 }, TransformBundle::default()))
+            .set_parent(entity)
+            .id();
+        for i in 1..=20 {
+            step(&mut app, entity, Vec3::X * i as f32);
+            
+            // Rebuild the rider's world position from this frame's transforms
+            let world = app.world();
+            let rider_world = world.get::<Transform>(entity).unwrap().compute_affine()
+                * world.get::<Transform>(rider).unwrap().compute_affine();
+            let Some(expected) = trail(&app, entity).sample_at(0.5) else {
+                continue;
+            };
+            let actual = Vec3::from(rider_world.translation);
+            assert!(actual.distance(expected) < 1e-3, "rider at {actual}, trail at {expected}");
+        }
+    }
+    
+    #[test]
+    fn fade_out_inserted_by_hand_despawns_its_mesh() {
+        let mut app = app();
+        let mut path_trail = test_trail();
+        path_trail.push_path([Vec3::ZERO, Vec3::X, Vec3::X * 2.0]);
+        let mesh = app.world_mut().resource_mut::<Assets<Mesh>>().add(path_trail.to_mesh());
+        let entity = app.world_mut().spawn((mesh.clone(), TrailFadeOut::new(0.1))).id();
+        
+        app.update();
+        app.update();
+        let progress = app.world().get::<TrailFadeOut>(entity).unwrap().progress();
+        assert!(progress > 0.0 && progress < 1.0);
+        
+        for _ in 0..10 {
+            app.update();
+        }
+        assert!(app.world().get_entity(entity).is_none());
+        assert!(app.world().resource::<Assets<Mesh>>().get(&mesh).is_none());
+    }
+    
+    /// Manual trail painting a 16 pixel decal of the 16 by 16 unit ground
+    fn spawn_decal_trail(app: &mut App, reference_frame: Option<Entity>) -> Entity {
+        let image = app
+            .world_mut()
+            .resource_mut::<Assets<Image>>()
+            .add(TrailDecal::blank_image(16, 16));
+        let projection = DecalProjection::ground(Vec3::ZERO, Vec2::splat(16.0));
+        let mut decal_trail = test_trail();
+        decal_trail.emit_mode = EmitMode::Manual;
+        decal_trail.reference_frame = reference_frame;
+        let decal = TrailDecal::new(image, projection, 1.5, Color::WHITE);
+        app.world_mut()
+            .spawn((decal_trail, decal, TransformBundle::default()))
+            .id()
+    }
+    
+    fn decal_alpha(app: &App, entity: Entity, x: usize, y: usize) -> u8 {
+        let handle = &app.world().get::<TrailDecal>(entity).unwrap().image;
+        let image = app.world().resource::<Assets<Image>>().get(handle).unwrap();
+        image.data[(y * image.width() as usize + x) * 4 + 3]
+    }
+    
+    #[test]
+    fn decal_paints_points_through_reference_frame() {
+        let mut app = app();
+        let frame = app
+            .world_mut()
+            .spawn(GlobalTransform::from_xyz(4.0, 0.0, 0.0))
+            .id();
+        let entity = spawn_decal_trail(&mut app, Some(frame));
+        let clock = trail(&app, entity).clock();
+        trail_mut(&mut app, entity).push_point(Vec3::ZERO, clock);
+        app.update();
+        
+        // The frame's origin is 4 units along X, a quarter of the image across
+        assert!(decal_alpha(&app, entity, 12, 8) > 0);
+        assert_eq!(decal_alpha(&app, entity, 8, 8), 0);
+    }
+    
+    #[test]
+    fn decal_starts_a_new_stroke_after_clear() {
+        let mut app = app();
+        let entity = spawn_decal_trail(&mut app, None);
+        let clock = trail(&app, entity).clock();
+        trail_mut(&mut app, entity).push_point(Vec3::X * -6.0, clock);
+        app.update();
+        trail_mut(&mut app, entity).clear();
+        app.update();
+        let clock = trail(&app, entity).clock();
+        trail_mut(&mut app, entity).push_point(Vec3::X * 6.0, clock);
+        app.update();
+        
+        assert!(decal_alpha(&app, entity, 2, 8) > 0);
+        assert!(decal_alpha(&app, entity, 14, 8) > 0);
+        assert_eq!(decal_alpha(&app, entity, 8, 8), 0, "stroke bridged the cleared gap");
+    }
+    
+    #[test]
+    fn spatial_query_with_huge_radius_finds_nearest_point() {
+        let mut app = app();
+        let mut path_trail = test_trail();
+        path_trail.emit_mode = EmitMode::Manual;
+        path_trail.push_path([Vec3::ZERO, Vec3::X * 5.0, Vec3::new(5.0, 0.0, 5.0)]);
+        app.world_mut().spawn((path_trail, IndexedTrail, TransformBundle::default()));
+        app.update();
+        
+        // A radius spanning billions of cells only visits the occupied ones
+        let index = app.world().resource::<TrailSpatialIndex>();
+        let (_, nearest) = index.nearest_trail_point(Vec3::splat(100.0), 1e9).unwrap();
+        assert_eq!(nearest, Vec3::new(5.0, 0.0, 5.0));
+        assert!(index.nearest_trail_point(Vec3::splat(100.0), 10.0).is_none());
+    }
+    
+    #[test]
+    fn expired_trails_free_their_meshes() {
+        let mut app = app();
+        app.update();
+        let baseline = app.world().resource::<Assets<Mesh>>().len();
+        
+        let entities: Vec<Entity> = (0..100)
+            .map(|_| spawn_trail(&mut app, test_trail().with_max_age(Some(0.1)), Vec3::ZERO))
+            .collect();
+        for i in 1..=4 {
+            for &entity in &entities {
+                app.world_mut().get_mut::<Transform>(entity).unwrap().translation =
+                    Vec3::X * i as f32;
+            }
+            app.update();
+        }
+        assert_eq!(app.world().resource::<Assets<Mesh>>().len(), baseline + 100);
+        
+        // Hold still until every point has expired
+        for _ in 0..20 {
+            app.update();
+        }
+        assert_eq!(mesh_entity_count(&mut app), 0);
+        assert_eq!(app.world().resource::<Assets<Mesh>>().len(), baseline);
+    }
+    
+    #[test]
+    fn pushed_points_generate_mesh_without_update_trails() {
+        let mut app = app();
+        let mut trail = test_trail();
+        for i in 0..4 {
+            trail.push_point(Vec3::X * i as f32, i as f32 * 0.1);
+        }
+        let entity = app.world_mut().spawn(trail).id();
+        
+        app.world_mut().run_system_once(generate_trail_meshes);
+        
+        assert_eq!(mesh_positions(trail_mesh(&app, entity)).len(), 4 * 2);
+    }
+    
+    #[test]
+    fn triangle_strip_drops_index_buffer() {
+        let [list, strip] = [false, true].map(|triangle_strip| {
+            let mut trail = test_trail();
+            trail.triangle_strip = triangle_strip;
+            for i in 0..10 {
+                trail.push_point(Vec3::X * i as f32, i as f32 * 0.1);
+            }
+            trail.to_mesh()
+        });
+        
+        assert_eq!(list.primitive_topology(), PrimitiveTopology::TriangleList);
+        assert_eq!(strip.primitive_topology(), PrimitiveTopology::TriangleStrip);
+        assert_eq!(list.indices().map(Indices::len), Some(9 * 6));
+        assert!(strip.indices().is_none());
+        assert_eq!(strip.count_vertices(), list.count_vertices());
+        assert_eq!(strip.get_vertex_buffer_data().len(), list.get_vertex_buffer_data().len());
+    }
+    
+    #[test]
+    fn head_only_attached_while_emitting_on_its_own() {
+        let mut app = app();
+        assert!(!test_trail().attach_head);
+        let mut attached = test_trail();
+        attached.attach_head = true;
+        let entity = spawn_trail(&mut app, attached, Vec3::ZERO);
+        for i in 1..=4 {
+            step(&mut app, entity, Vec3::X * i as f32);
+        }
+        trail_mut(&mut app, entity).emit_mode = EmitMode::Manual;
+        step(&mut app, entity, Vec3::X * 10.0);
+        
+        let point_count = trail(&app, entity).point_count();
+        let positions = mesh_positions(trail_mesh(&app, entity));
+        assert_eq!(positions.len(), point_count * 2);
+        assert!(positions.iter().all(|position| position.x <= 4.0));
+    }
+    
+    /// Mesh entities, and with that draw calls, for 500 trails sharing a
+    /// material with and without batching
+    #[test]
+    fn batching_draws_shared_material_trails_at_once() {
+        for (batched, draw_calls) in [(false, 500), (true, 1)] {
+            let mut app = app();
+            let entities: Vec<Entity> = (0..500)
+                .map(|i| {
+                    let mut trail = test_trail();
+                    trail.batched = batched;
+                    spawn_trail(&mut app, trail, Vec3::Z * i as f32)
+                })
+                .collect();
+            for i in 1..=4 {
+                for (z, &entity) in entities.iter().enumerate() {
+                    app.world_mut().get_mut::<Transform>(entity).unwrap().translation =
+                        Vec3::new(i as f32, 0.0, z as f32);
+                }
+                app.update();
+            }
+            assert_eq!(mesh_entity_count(&mut app), draw_calls);
+            
+            if batched {
+                let meshes = app.world().resource::<Assets<Mesh>>();
+                let own_mesh = trail(&app, entities[0]).mesh.as_ref().unwrap();
+                let usage = meshes.get(own_mesh).unwrap().asset_usage;
+                assert_eq!(usage, RenderAssetUsages::MAIN_WORLD);
+                
+                // Nothing moved, so neither the trails nor the batch are rebuilt
+                app.update();
+                let modified = app
+                    .world()
+                    .resource::<Events<AssetEvent<Mesh>>>()
+                    .iter_current_update_events()
+                    .filter(|event| matches!(event, AssetEvent::Modified { .. }))
+                    .count();
+                assert_eq!(modified, 0);
+            }
+        }
+    }
+    
+    #[test]
+    fn duplicate_points_give_finite_mesh() {
+        for positions in [[Vec3::ZERO; 4], [Vec3::ZERO, Vec3::ZERO, Vec3::X, Vec3::X]] {
+            let mut trail = test_trail();
+            // Keep the duplicates rather than merging them
+            trail.merge_epsilon = -1.0;
+            for (i, position) in positions.into_iter().enumerate() {
+                trail.push_point(position, i as f32 * 0.1);
+            }
+            let mesh = trail.to_mesh();
+            
+            for attribute in [Mesh::ATTRIBUTE_POSITION, Mesh::ATTRIBUTE_NORMAL] {
+                let Some(VertexAttributeValues::Float32x3(values)) = mesh.attribute(attribute)
+                else {
+                    panic!("trail mesh is missing a vertex attribute");
+                };
+                assert_eq!(values.len(), positions.len() * 2);
+                assert!(values.iter().flatten().all(|value| value.is_finite()));
+            }
+        }
+    }
+    
+    #[test]
+    fn trail_material_renders_both_faces() {
+        let material = trail_material(Color::WHITE);
+        assert!(material.double_sided);
+        assert_eq!(material.cull_mode, None);
+    }
+    
+    #[test]
+    fn spawned_trail_gets_double_sided_material() {
+        let mut app = app();
+        let color = Color::srgba(1.0, 0.5, 0.0, 0.5);
+        let entity = app.world_mut().commands().spawn_trail(color, test_trail()).id();
+        app.world_mut().flush();
+        app.update();
+        
+        let handle = &trail(&app, entity).material;
+        let material = app.world().resource::<Assets<StandardMaterial>>().get(handle).unwrap();
+        assert_eq!(material.base_color, color);
+        assert!(material.double_sided);
+        assert_eq!(material.cull_mode, None);
+    }
+    
+    #[test]
+    fn double_sided_trail_updates_its_material() {
+        let mut app = app();
+        let material = app
+            .world_mut()
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial::default());
+        let mut trail = test_trail();
+        trail.material = material.clone();
+        trail.double_sided = true;
+        spawn_trail(&mut app, trail, Vec3::ZERO);
+        app.update();
+        
+        let material = app.world().resource::<Assets<StandardMaterial>>().get(&material).unwrap();
+        assert!(material.double_sided);
+        assert_eq!(material.cull_mode, None);
+    }
+    
+    #[test]
+    fn double_sided_ignored_with_custom_material() {
+        let mut app = app();
+        app.add_plugins(TrailMaterialPlugin::<StandardMaterial>::default());
+        let mut materials = app.world_mut().resource_mut::<Assets<StandardMaterial>>();
+        let material = materials.add(StandardMaterial::default());
+        let custom_material = materials.add(StandardMaterial::default());
+        let mut trail = test_trail();
+        trail.material = material.clone();
+        trail.double_sided = true;
+        let entity = spawn_trail(&mut app, trail, Vec3::ZERO);
+        app.world_mut().entity_mut(entity).insert(TrailMaterial(custom_material));
+        
+        let warnings = count_warnings(|| {
+            for i in 1..=4 {
+                step(&mut app, entity, Vec3::X * i as f32);
+            }
+        });
+        
+        assert_eq!(warnings, 1);
+        let materials = app.world().resource::<Assets<StandardMaterial>>();
+        assert!(!materials.get(&material).unwrap().double_sided);
+    }
+    
+    #[test]
+    fn aabb_encloses_all_vertices() {
+        let mut app = app();
+        let entity = spawn_trail(&mut app, test_trail(), Vec3::ZERO);
+        for i in 1..=8 {
+            let angle = i as f32 * 0.5;
+            step(&mut app, entity, Vec3::new(angle.cos(), angle * 0.2, angle.sin()) * 3.0);
+        }
+        
+        let mesh_entity = trail(&app, entity).mesh_entity().unwrap();
+        let aabb = app.world().get::<Aabb>(mesh_entity).expect("mesh entity has no Aabb");
+        assert_encloses(aabb, trail_mesh(&app, entity));
+    }
+    
+    #[test]
+    fn particle_bounds_follow_rebuilt_mesh() {
+        let mut app = app();
+        let particles = app.world_mut().spawn(ParticleTrails::new(test_trail())).id();
+        for i in 1..=40 {
+            app.world_mut()
+                .get_mut::<ParticleTrails>(particles)
+                .unwrap()
+                .set_positions(&[Vec3::X * i as f32]);
+            app.update();
+        }
+        
+        // The mesh is overwritten in place, which Bevy's bounds systems never see
+        let (handle, aabb) = app
+            .world_mut()
+            .query::<(&Handle<Mesh>, &Aabb)>()
+            .single(app.world());
+        let mesh = app.world().resource::<Assets<Mesh>>().get(handle).unwrap();
+        assert!(aabb.max().x > 30.0);
+        assert_encloses(aabb, mesh);
+    }
+    
+    #[test]
+    fn arc_length_sums_segments() {
+        let mut trail = test_trail();
+        let positions = [Vec3::ZERO, Vec3::X, Vec3::new(1.0, 2.0, 0.0)];
+        for (i, position) in positions.into_iter().enumerate() {
+            trail.push_point(position, i as f32 * 0.1);
+        }
+        assert_eq!(trail.arc_length(), 3.0);
+        assert_eq!(trail.arc_length(), trail.length());
+    }
+    
+    #[test]
+    fn still_trail_keeps_its_mesh() {
+        let mut app = app();
+        let entity = spawn_trail(&mut app, test_trail(), Vec3::ZERO);
+        for i in 1..=4 {
+            step(&mut app, entity, Vec3::X * i as f32);
+        }
+        let mesh = trail(&app, entity).mesh.clone().unwrap();
+        
+        for _ in 0..5 {
+            app.update();
+            assert_eq!(trail(&app, entity).mesh.as_ref(), Some(&mesh));
+            assert!(!trail(&app, entity).is_dirty());
+            let rebuilt = app
+                .world()
+                .resource::<Events<AssetEvent<Mesh>>>()
+                .iter_current_update_events()
+                .any(|event| event.is_modified(&mesh));
+            assert!(!rebuilt);
+        }
+    }
+    
+    #[test]
+    fn push_path_fits_within_max_age() {
+        let mut app = app();
+        let path: Vec<Vec3> = (0..200).map(|i| Vec3::X * i as f32).collect();
+        let path_trail = Trail::builder()
+            .max_points(300)
+            .emit_rate(10.0)
+            .initial_points(path.clone())
+            .build();
+        let entity = spawn_trail(&mut app, path_trail, Vec3::X * 199.0);
+        app.update();
+        // Only the tail is old enough to expire on the first update
+        assert!(trail(&app, entity).point_count() >= 198);
+        
+        // A path beyond max_points is spaced over the points that are kept
+        let mut capped = test_trail();
+        capped.max_points = 20;
+        capped.emit_rate = 1.0;
+        capped.max_age = Some(5.0);
+        capped.push_path(path.iter().copied());
+        assert_eq!(capped.point_count(), 20);
+        let span = capped.clock() - capped.points[0].timestamp;
+        assert!((span - 5.0).abs() < 1e-4, "kept points span {span} seconds");
+        assert_eq!(capped.points[0].position, path[180]);
+        
+        let mut trail = test_trail();
+        trail.emit_rate = 0.0;
+        trail.push_path(path);
+        assert!(trail.points.iter().all(|point| point.timestamp == trail.clock()));
+    }
+    
+    /// Right vector of every vertex pair, keyed by the centerline position
+    fn mesh_rights(mesh: &Mesh) -> Vec<(Vec3, Vec3)> {
+        mesh_positions(mesh)
+            .chunks(2)
+            .map(|edge| ((edge[0] + edge[1]) * 0.5, (edge[1] - edge[0]).normalize()))
+            .collect()
+    }
+    
+    #[test]
+    fn parallel_transport_keeps_roll_as_tail_expires() {
+        let mut app = app();
+        let mut bend = test_trail();
+        bend.max_points = 12;
+        bend.orient = OrientMode::ParallelTransport;
+        let entity = spawn_trail(&mut app, bend, Vec3::ZERO);
+        
+        // Run along X, then curve over a quarter circle to head straight up,
+        // where the up axis gives no right vector of its own
+        let bend_point = |i: usize| match i {
+            0..=10 => Vec3::X * i as f32,
+            11..=20 => {
+                let angle = (i - 10) as f32 * 0.1 * std::f32::consts::FRAC_PI_2;
+                Vec3::new(10.0 + angle.sin() * 4.0, (1.0 - angle.cos()) * 4.0, 0.0)
+            }
+            _ => Vec3::new(14.0, (i - 16) as f32, 0.0),
+        };
+        
+        for i in 1..=3 {
+            step(&mut app, entity, bend_point(i));
+        }
+        
+        // Each rebuild drops the tail, which sweeps it through the bend
+        let mut previous: Option<Vec<(Vec3, Vec3)>> = None;
+        for i in 4..=40 {
+            step(&mut app, entity, bend_point(i));
+            let rights = mesh_rights(trail_mesh(&app, entity));
+            if let Some(previous) = &previous {
+                for (center, right) in &rights {
+                    let Some((_, before)) =
+                        previous.iter().find(|(other, _)| other.distance(*center) < 1e-4)
+                    else {
+                        continue;
+                    };
+                    let angle = right.angle_between(*before);
+                    assert!(angle < 0.05, "point at {center} rolled by {angle} on frame {i}");
+                }
+            }
+            previous = Some(rights);
+        }
+        
+        // The vertical head still carries the roll transported into it
+        let head = trail(&app, entity).points.back().unwrap();
+        assert!(head.right.unwrap().dot(Vec3::Z).abs() > 0.99);
+    }
+}
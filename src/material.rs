@@ -0,0 +1,253 @@
+use bevy::asset::load_internal_asset;
+use bevy::pbr::{MaterialPipeline, MaterialPipelineKey};
+use bevy::prelude::*;
+use bevy::render::mesh::MeshVertexBufferLayoutRef;
+use bevy::render::render_resource::{
+    AsBindGroup, RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError,
+};
+use std::marker::PhantomData;
+
+use crate::{Trail, TrailSystemSet, ATTRIBUTE_EDGE_DISTANCE};
+
+const SOFT_EDGE_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0x5f1c_9d2e_7a43_4b80_9e61_0c3d_b2a7_e415);
+const SOFT_DEPTH_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0x2b8e_41d7_c950_4f3a_86d2_e71f_0a94_c36b);
+
+/// Lets [`TrailMaterial<M>`] draw trails with the material type `M`. Add it
+/// alongside the `MaterialPlugin` for `M`.
+pub struct TrailMaterialPlugin<M: Material>(PhantomData<M>);
+
+impl<M: Material> Default for TrailMaterialPlugin<M> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<M: Material> Plugin for TrailMaterialPlugin<M> {
+    fn build(&self, app: &mut App) {
+        app.observe(mark_custom_trail_material::<M>)
+            .observe(restore_standard_trail_material::<M>)
+            .add_systems(
+                Update,
+                apply_trail_materials::<M>
+                    .after(crate::sync_trail_mesh_entities)
+                    .in_set(TrailSystemSet::GenerateMesh),
+            );
+    }
+}
+
+/// Draws the [`Trail`] on the same entity with a custom material instead of
+/// its `StandardMaterial`, e.g. an unlit scrolling shader. Requires
+/// [`TrailMaterialPlugin`] for `M`. Batched and particle trails always use the
+/// `StandardMaterial`. While it's present, [`Trail::double_sided`] and the
+/// `color` of a trail config are ignored with a warning, since they only
+/// change the `StandardMaterial`.
+#[derive(Component, Clone, Debug)]
+pub struct TrailMaterial<M: Material>(pub Handle<M>);
+
+fn apply_trail_materials<M: Material>(
+    mut commands: Commands,
+    mut trail_query: Query<(&mut Trail, &TrailMaterial<M>)>,
+    material_query: Query<&Handle<M>>,
+) {
+    for (mut trail, material) in trail_query.iter_mut() {
+        if !trail.custom_material {
+            trail.custom_material = true;
+        }
+        let Some(mesh_entity) = trail.mesh_entity else {
+            continue;
+        };
+        if material_query
+            .get(mesh_entity)
+            .is_ok_and(|current| *current == material.0)
+        {
+            continue;
+        }
+        if let Some(mut entity_commands) = commands.get_entity(mesh_entity) {
+            entity_commands
+                .remove::<Handle<StandardMaterial>>()
+                .insert(material.0.clone());
+        }
+    }
+}
+
+/// Flag the trail as soon as its [`TrailMaterial`] is added, so settings that
+/// only apply to the `StandardMaterial` are skipped from the first frame
+fn mark_custom_trail_material<M: Material>(
+    trigger: Trigger<OnAdd, TrailMaterial<M>>,
+    mut trail_query: Query<&mut Trail>,
+) {
+    if let Ok(mut trail) = trail_query.get_mut(trigger.entity()) {
+        trail.custom_material = true;
+    }
+}
+
+/// Hand the mesh back to the trail's `StandardMaterial` when its
+/// [`TrailMaterial`] is removed
+fn restore_standard_trail_material<M: Material>(
+    trigger: Trigger<OnRemove, TrailMaterial<M>>,
+    mut commands: Commands,
+    mut trail_query: Query<&mut Trail>,
+) {
+    let Ok(mut trail) = trail_query.get_mut(trigger.entity()) else {
+        return;
+    };
+    trail.custom_material = false;
+    if let Some(mut entity_commands) = trail.mesh_entity.and_then(|e| commands.get_entity(e)) {
+        entity_commands.remove::<Handle<M>>();
+    }
+}
+
+/// Registers [`SoftEdgeMaterial`] and its shader
+pub struct SoftEdgeMaterialPlugin;
+
+impl Plugin for SoftEdgeMaterialPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            SOFT_EDGE_SHADER_HANDLE,
+            "shaders/soft_edge.wgsl",
+            Shader::from_wgsl
+        );
+        app.add_plugins(MaterialPlugin::<SoftEdgeMaterial>::default());
+    }
+}
+
+/// Unlit trail material that feathers the alpha towards the ribbon edges.
+///
+/// Requires meshes carrying [`ATTRIBUTE_EDGE_DISTANCE`], i.e. trails with
+/// `edge_attribute` enabled.
+#[derive(Asset, TypePath, AsBindGroup, Clone, Debug)]
+pub struct SoftEdgeMaterial {
+    /// Base color, including alpha
+    #[uniform(0)]
+    pub color: LinearRgba,
+    /// Distance from the edge (in world units) over which alpha fades in
+    #[uniform(1)]
+    pub softness: f32,
+}
+
+impl Default for SoftEdgeMaterial {
+    fn default() -> Self {
+        Self {
+            color: LinearRgba::WHITE,
+            softness: 0.1,
+        }
+    }
+}
+
+impl Material for SoftEdgeMaterial {
+    fn vertex_shader() -> ShaderRef {
+        SOFT_EDGE_SHADER_HANDLE.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        SOFT_EDGE_SHADER_HANDLE.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Blend
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        let vertex_layout = layout.0.get_layout(&[
+            Mesh::ATTRIBUTE_POSITION.at_shader_location(0),
+            ATTRIBUTE_EDGE_DISTANCE.at_shader_location(1),
+        ])?;
+        descriptor.vertex.buffers = vec![vertex_layout];
+        // Ribbons are visible from both sides
+        descriptor.primitive.cull_mode = None;
+        Ok(())
+    }
+}
+
+/// Registers [`SoftDepthMaterial`] and its shader
+pub struct SoftDepthMaterialPlugin;
+
+impl Plugin for SoftDepthMaterialPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            SOFT_DEPTH_SHADER_HANDLE,
+            "shaders/soft_depth.wgsl",
+            Shader::from_wgsl
+        );
+        app.add_plugins(MaterialPlugin::<SoftDepthMaterial>::default());
+    }
+}
+
+/// Unlit trail material that fades out where the ribbon nears or passes
+/// behind opaque geometry, instead of clipping hard at the intersection.
+///
+/// The fade samples the depth prepass, so the rendering camera needs a
+/// `DepthPrepass` component. Without one, or on targets that can't sample the
+/// prepass such as WebGL2, the ribbon draws unfaded; use
+/// [`SoftDepthMaterial::fallback`] there for an equivalent
+/// [`StandardMaterial`].
+#[derive(Asset, TypePath, AsBindGroup, Clone, Debug)]
+pub struct SoftDepthMaterial {
+    /// Base color, including alpha
+    #[uniform(0)]
+    pub color: LinearRgba,
+    /// View-space distance in front of the scene over which alpha fades in
+    #[uniform(1)]
+    pub fade_distance: f32,
+}
+
+impl Default for SoftDepthMaterial {
+    fn default() -> Self {
+        Self {
+            color: LinearRgba::WHITE,
+            fade_distance: 0.5,
+        }
+    }
+}
+
+impl SoftDepthMaterial {
+    /// Unlit, double-sided [`StandardMaterial`] with the same color, for
+    /// cameras without a depth prepass
+    pub fn fallback(&self) -> StandardMaterial {
+        StandardMaterial {
+            base_color: self.color.into(),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            cull_mode: None,
+            double_sided: true,
+            ..default()
+        }
+    }
+}
+
+impl Material for SoftDepthMaterial {
+    fn vertex_shader() -> ShaderRef {
+        SOFT_DEPTH_SHADER_HANDLE.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        SOFT_DEPTH_SHADER_HANDLE.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Blend
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        let vertex_layout = layout
+            .0
+            .get_layout(&[Mesh::ATTRIBUTE_POSITION.at_shader_location(0)])?;
+        descriptor.vertex.buffers = vec![vertex_layout];
+        descriptor.primitive.cull_mode = None;
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+use crate::slot::MeshSlot;
+use crate::{Trail, TrailPoint};
+
+/// Many short trails, one per particle, updated from positions fed each frame
+/// and drawn as a single merged mesh. Use it for swarms of tiny trails such as
+/// firework sparks, where an entity per trail would be too costly.
+#[derive(Component)]
+pub struct ParticleTrails {
+    /// Emission, lifetime and ribbon settings shared by every particle. Its
+    /// own points are unused.
+    pub settings: Trail,
+    /// Latest position of each particle
+    positions: Vec<Vec3>,
+    /// Point buffer of each particle, at least as many as `positions`
+    particles: Vec<VecDeque<TrailPoint>>,
+    slot: MeshSlot,
+}
+
+impl ParticleTrails {
+    pub fn new(settings: Trail) -> Self {
+        Self {
+            settings,
+            positions: Vec::new(),
+            particles: Vec::new(),
+            slot: MeshSlot::default(),
+        }
+    }
+
+    /// Feed the current position of every particle, indexed consistently
+    /// between frames. Particles left out stop emitting and their trails
+    /// expire.
+    pub fn set_positions(&mut self, positions: &[Vec3]) {
+        self.positions.clear();
+        self.positions.extend_from_slice(positions);
+        if self.particles.len() < positions.len() {
+            self.particles.resize_with(positions.len(), VecDeque::new);
+        }
+    }
+
+    /// Number of particle trails that still have points
+    pub fn active_particles(&self) -> usize {
+        self.particles
+            .iter()
+            .filter(|points| !points.is_empty())
+            .count()
+    }
+
+    /// Drop every particle's points
+    pub fn clear(&mut self) {
+        self.positions.clear();
+        self.particles.clear();
+    }
+
+    /// Run `f` on the shared settings with particle `index`'s points swapped
+    /// in, so the single trail code paths handle every particle
+    fn with_particle<R>(&mut self, index: usize, f: impl FnOnce(&mut Trail) -> R) -> R {
+        std::mem::swap(&mut self.settings.points, &mut self.particles[index]);
+        let result = f(&mut self.settings);
+        std::mem::swap(&mut self.settings.points, &mut self.particles[index]);
+        result
+    }
+}
+
+pub(crate) fn update_particle_trails(
+    time: Res<Time>,
+    mut particle_query: Query<&mut ParticleTrails>,
+) {
+    for mut particle_trails in particle_query.iter_mut() {
+        let particle_trails = &mut *particle_trails;
+        let settings = &mut particle_trails.settings;
+        let emit = settings.tick(time.delta());
+        let (clock, frame) = (settings.clock, settings.frame);
+
+        for index in 0..particle_trails.particles.len() {
+            let position = particle_trails.positions.get(index).copied();
+            particle_trails.with_particle(index, |trail| {
+                if let (true, Some(position)) = (emit, position) {
+                    trail.record_point(TrailPoint {
+                        position,
+                        timestamp: clock,
+                        frame,
+                        right: None,
+                    });
+                }
+                trail.expire_aged(1.0);
+            });
+        }
+
+        // Forget expired particles past the end of the fed positions
+        while particle_trails.particles.len() > particle_trails.positions.len()
+            && particle_trails
+                .particles
+                .last()
+                .is_some_and(VecDeque::is_empty)
+        {
+            particle_trails.particles.pop();
+        }
+    }
+}
+
+pub(crate) fn generate_particle_trail_meshes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut particle_query: Query<&mut ParticleTrails>,
+    camera_query: Query<&GlobalTransform, With<Camera3d>>,
+) {
+    let view = camera_query.iter().next();
+
+    for mut particle_trails in particle_query.iter_mut() {
+        let min_points = particle_trails.settings.min_render_points();
+        let mut merged: Option<Mesh> = None;
+        for index in 0..particle_trails.particles.len() {
+            if particle_trails.particles[index].len() < min_points {
+                continue;
+            }
+            // Strips can't be merged without bridging triangles between them
+            let mesh = particle_trails.with_particle(index, |trail| {
+                let triangle_strip = std::mem::replace(&mut trail.triangle_strip, false);
+                let mesh = trail.build_mesh(view, None);
+                trail.triangle_strip = triangle_strip;
+                mesh
+            });
+            match &mut merged {
+                Some(merged) => merged.merge(&mesh),
+                None => merged = Some(mesh),
+            }
+        }
+
+        let particle_trails = &mut *particle_trails;
+        let Some(mesh) = merged else {
+            particle_trails.slot.clear(&mut commands, &mut meshes);
+            continue;
+        };
+        let settings = &particle_trails.settings;
+        particle_trails
+            .slot
+            .show(&mut commands, &mut meshes, mesh, |handle| {
+                (
+                    PbrBundle {
+                        mesh: handle,
+                        material: settings.material.clone(),
+                        ..default()
+                    },
+                    settings.render_layers.clone().unwrap_or_default(),
+                )
+            });
+    }
+}
+
+/// Despawn the merged mesh entity of particle trails along with their owner or
+/// the removed `ParticleTrails`
+pub(crate) fn despawn_removed_particle_trail_meshes(
+    trigger: Trigger<OnRemove, ParticleTrails>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut particle_query: Query<&mut ParticleTrails>,
+) {
+    if let Ok(mut particle_trails) = particle_query.get_mut(trigger.entity()) {
+        particle_trails.slot.clear(&mut commands, &mut meshes);
+    }
+}
@@ -0,0 +1,112 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{sample_keyframes, sort_keyframes, RonAssetError, Trail};
+
+/// Width, alpha and color curves along a trail, authored once in a
+/// `.profile.ron` file and shared by handle through [`Trail::profile`].
+///
+/// Each curve is a list of `(progress, value)` keyframes sorted by progress,
+/// which runs from 0 at the tail to 1 at the head. Values are interpolated
+/// linearly and held past the first and last keyframe. Empty curves leave that
+/// property at the trail's default.
+#[derive(Asset, TypePath, Clone, Debug, Default, Deserialize, Serialize)]
+pub struct TrailProfile {
+    /// Full ribbon width, replacing the taper
+    #[serde(default)]
+    pub width: Vec<(f32, f32)>,
+    /// Vertex alpha
+    #[serde(default)]
+    pub alpha: Vec<(f32, f32)>,
+    /// sRGB vertex color
+    #[serde(default)]
+    pub color: Vec<(f32, [f32; 3])>,
+}
+
+impl TrailProfile {
+    /// Sort every curve by progress, dropping keyframes with a non-finite
+    /// progress. Loaded profiles are already sorted.
+    pub fn sort(&mut self) {
+        sort_keyframes(&mut self.width);
+        sort_keyframes(&mut self.alpha);
+        sort_keyframes(&mut self.color);
+    }
+
+    /// Width at `progress`, `None` without width keyframes
+    pub fn width_at(&self, progress: f32) -> Option<f32> {
+        sample_keyframes(&self.width, progress, |a, b, t| a + (b - a) * t)
+    }
+
+    /// Linear vertex color and alpha at `progress`, `None` when neither the
+    /// color nor the alpha curve has keyframes
+    pub fn color_at(&self, progress: f32) -> Option<LinearRgba> {
+        if self.color.is_empty() && self.alpha.is_empty() {
+            return None;
+        }
+        let [r, g, b] = sample_keyframes(&self.color, progress, |a, b, t| {
+            Vec3::from(a).lerp(Vec3::from(b), t).to_array()
+        })
+        .unwrap_or([1.0; 3]);
+        let alpha =
+            sample_keyframes(&self.alpha, progress, |a, b, t| a + (b - a) * t).unwrap_or(1.0);
+        Some(Color::srgba(r, g, b, alpha).to_linear())
+    }
+}
+
+/// Loads [`TrailProfile`]s from RON
+#[derive(Default)]
+pub struct TrailProfileLoader;
+
+impl AssetLoader for TrailProfileLoader {
+    type Asset = TrailProfile;
+    type Settings = ();
+    type Error = RonAssetError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let mut profile: TrailProfile = ron::de::from_bytes(&bytes)?;
+        profile.sort();
+        Ok(profile)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["profile.ron"]
+    }
+}
+
+/// Rebuild trails whose profile was loaded or hot-reloaded
+pub(crate) fn refresh_profiled_trails(
+    mut events: EventReader<AssetEvent<TrailProfile>>,
+    mut trail_query: Query<&mut Trail>,
+) {
+    let changed: Vec<_> = events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Added { id }
+            | AssetEvent::Modified { id }
+            | AssetEvent::LoadedWithDependencies { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    if changed.is_empty() {
+        return;
+    }
+
+    for mut trail in trail_query.iter_mut() {
+        let uses_changed = trail
+            .profile
+            .as_ref()
+            .is_some_and(|profile| changed.contains(&profile.id()));
+        if uses_changed {
+            trail.dirty = true;
+        }
+    }
+}
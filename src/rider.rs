@@ -0,0 +1,65 @@
+use bevy::math::Affine3A;
+use bevy::prelude::*;
+
+use crate::Trail;
+
+/// Places a child of a [`Trail`] entity at a fixed fraction of the way along
+/// the trail each frame, facing its direction of travel. Use it to decorate
+/// trails with real entities such as lanterns or beads.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct TrailRider {
+    /// Arc length fraction along the trail, from the tail at 0 to the head at 1
+    pub t: f32,
+}
+
+pub(crate) fn update_trail_riders(
+    mut rider_query: Query<(&TrailRider, &Parent, &mut Transform)>,
+    trail_query: Query<&Trail>,
+    hierarchy_query: Query<(&Transform, Option<&Parent>), Without<TrailRider>>,
+    frame_query: Query<&GlobalTransform>,
+) {
+    for (rider, parent, mut transform) in rider_query.iter_mut() {
+        let Ok(trail) = trail_query.get(parent.get()) else {
+            continue;
+        };
+        let Some(position) = trail.sample_at(rider.t) else {
+            continue;
+        };
+
+        // Points live in the reference frame's space when one is set
+        let frame = trail
+            .frame_entity()
+            .and_then(|e| frame_query.get(e).ok())
+            .map(GlobalTransform::affine);
+        let to_world = |v: Vec3| frame.map_or(v, |frame| frame.transform_point3(v));
+        let world_position = to_world(position);
+        let mut world = Transform::from_translation(world_position);
+        if let Some(direction) = trail.direction_at(rider.t) {
+            let world_direction = to_world(position + direction) - world_position;
+            world.look_to(world_direction, trail.up_axis);
+        }
+
+        // Riders are children, so convert back into the trail entity's space
+        let parent_affine = current_affine(parent.get(), &hierarchy_query);
+        let local = parent_affine.inverse() * world.compute_affine();
+        let local = Transform::from_matrix(local.into());
+        transform.translation = local.translation;
+        transform.rotation = local.rotation;
+    }
+}
+
+/// World transform of `entity` composed from this frame's `Transform`s, as its
+/// `GlobalTransform` is only propagated after `Update` and would lag a frame
+/// behind a moving trail
+fn current_affine(
+    entity: Entity,
+    hierarchy_query: &Query<(&Transform, Option<&Parent>), Without<TrailRider>>,
+) -> Affine3A {
+    let mut affine = Affine3A::IDENTITY;
+    let mut current = Some(entity);
+    while let Some((transform, parent)) = current.and_then(|e| hierarchy_query.get(e).ok()) {
+        affine = transform.compute_affine() * affine;
+        current = parent.map(Parent::get);
+    }
+    affine
+}
@@ -0,0 +1,45 @@
+#import bevy_pbr::mesh_functions::{get_world_from_local, mesh_position_local_to_clip}
+#import bevy_pbr::prepass_utils
+#import bevy_pbr::view_transformations::depth_ndc_to_view_z
+
+@group(2) @binding(0) var<uniform> color: vec4<f32>;
+@group(2) @binding(1) var<uniform> fade_distance: f32;
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+};
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = mesh_position_local_to_clip(
+        get_world_from_local(vertex.instance_index),
+        vec4<f32>(vertex.position, 1.0),
+    );
+    return out;
+}
+
+@fragment
+fn fragment(
+#ifdef MULTISAMPLED
+    @builtin(sample_index) sample_index: u32,
+#endif
+    in: VertexOutput,
+) -> @location(0) vec4<f32> {
+#ifndef MULTISAMPLED
+    let sample_index = 0u;
+#endif
+    var alpha = 1.0;
+#ifdef DEPTH_PREPASS
+    // View-space distance between the ribbon and the opaque scene behind it
+    let scene_depth = prepass_utils::prepass_depth(in.clip_position, sample_index);
+    let separation = depth_ndc_to_view_z(in.clip_position.z) - depth_ndc_to_view_z(scene_depth);
+    alpha = clamp(separation / max(fade_distance, 0.0001), 0.0, 1.0);
+#endif
+    return vec4<f32>(color.rgb, color.a * alpha);
+}
@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+
+/// A generated mesh asset and the entity drawing it, for trails that spawn,
+/// update and despawn their mesh entity in the same system
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MeshSlot {
+    mesh: Option<Handle<Mesh>>,
+    entity: Option<Entity>,
+}
+
+impl MeshSlot {
+    /// Draw `mesh`, overwriting the current asset in place while both it and
+    /// the entity are alive. Otherwise `mesh` is added as a new asset and
+    /// `bundle` is inserted on the entity, or spawned when the entity is gone.
+    /// The entity's `Aabb` is refreshed either way, since Bevy only computes
+    /// bounds once and would otherwise cull the trail by its first mesh.
+    /// Returns the mesh entity.
+    pub(crate) fn show<B: Bundle>(
+        &mut self,
+        commands: &mut Commands,
+        meshes: &mut Assets<Mesh>,
+        mesh: Mesh,
+        bundle: impl FnOnce(Handle<Mesh>) -> B,
+    ) -> Entity {
+        let aabb = mesh.compute_aabb();
+        let entity = self
+            .entity
+            .filter(|&entity| commands.get_entity(entity).is_some());
+        if let (Some(entity), Some(existing)) = (
+            entity,
+            self.mesh.as_ref().and_then(|mesh| meshes.get_mut(mesh)),
+        ) {
+            *existing = mesh;
+            set_aabb(commands, entity, aabb);
+            return entity;
+        }
+
+        let handle = meshes.add(mesh);
+        if let Some(old) = self.mesh.replace(handle.clone()) {
+            meshes.remove(&old);
+        }
+        let entity = match entity {
+            Some(entity) => commands.entity(entity).insert(bundle(handle)).id(),
+            None => commands.spawn(bundle(handle)).id(),
+        };
+        self.entity = Some(entity);
+        set_aabb(commands, entity, aabb);
+        entity
+    }
+
+    /// Despawn the mesh entity and free its asset
+    pub(crate) fn clear(&mut self, commands: &mut Commands, meshes: &mut Assets<Mesh>) {
+        if let Some(entity_commands) = self.entity.take().and_then(|e| commands.get_entity(e)) {
+            entity_commands.despawn_recursive();
+        }
+        if let Some(mesh) = self.mesh.take() {
+            meshes.remove(&mesh);
+        }
+    }
+}
+
+/// Replace the entity's bounds, or drop them when the mesh has no positions
+/// so Bevy's bounds systems compute them again
+fn set_aabb(commands: &mut Commands, entity: Entity, aabb: Option<Aabb>) {
+    match aabb {
+        Some(aabb) => commands.entity(entity).insert(aabb),
+        None => commands.entity(entity).remove::<Aabb>(),
+    };
+}
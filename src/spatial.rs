@@ -0,0 +1,135 @@
+use bevy::math::I64Vec3;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::Trail;
+
+/// Marks a [`Trail`] whose points are registered in the [`TrailSpatialIndex`]
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct IndexedTrail;
+
+/// Spatial hash of the world positions of every [`IndexedTrail`]'s points,
+/// rebuilt each frame after the points are updated. Use it for broad-phase
+/// "am I near any trail" queries instead of scanning every trail.
+#[derive(Resource, Clone, Debug)]
+pub struct TrailSpatialIndex {
+    cell_size: f32,
+    cells: HashMap<IVec3, Vec<(Entity, Vec3)>>,
+    /// Smallest and largest occupied cell
+    bounds: Option<(IVec3, IVec3)>,
+}
+
+impl Default for TrailSpatialIndex {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl TrailSpatialIndex {
+    /// Create an empty index with cubic cells of `cell_size`. Choose it close
+    /// to the typical query radius.
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size: cell_size.max(f32::EPSILON),
+            cells: HashMap::default(),
+            bounds: None,
+        }
+    }
+
+    /// Edge length of a cell
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Trail entity and world position of the indexed point closest to
+    /// `position` within `radius`
+    pub fn nearest_trail_point(&self, position: Vec3, radius: f32) -> Option<(Entity, Vec3)> {
+        // Only visit cells that can hold points, so a huge radius stays cheap
+        let (occupied_min, occupied_max) = self.bounds?;
+        let min = self.cell(position - Vec3::splat(radius)).max(occupied_min);
+        let max = self.cell(position + Vec3::splat(radius)).min(occupied_max);
+        if min.cmpgt(max).any() {
+            return None;
+        }
+        let radius_squared = radius * radius;
+
+        let mut nearest = None;
+        let mut nearest_distance = radius_squared;
+        let mut visit = |points: &Vec<(Entity, Vec3)>| {
+            for &(entity, point) in points {
+                let distance = point.distance_squared(position);
+                if distance <= nearest_distance {
+                    nearest_distance = distance;
+                    nearest = Some((entity, point));
+                }
+            }
+        };
+
+        // Scan the occupied cells instead when there are fewer of them
+        let extent = max.as_i64vec3() - min.as_i64vec3() + I64Vec3::ONE;
+        let cell_count = extent.x.saturating_mul(extent.y).saturating_mul(extent.z);
+        if cell_count > self.cells.len() as i64 {
+            for (cell, points) in self.cells.iter() {
+                if cell.cmpge(min).all() && cell.cmple(max).all() {
+                    visit(points);
+                }
+            }
+        } else {
+            for x in min.x..=max.x {
+                for y in min.y..=max.y {
+                    for z in min.z..=max.z {
+                        if let Some(points) = self.cells.get(&IVec3::new(x, y, z)) {
+                            visit(points);
+                        }
+                    }
+                }
+            }
+        }
+        nearest
+    }
+
+    fn cell(&self, position: Vec3) -> IVec3 {
+        (position / self.cell_size).floor().as_ivec3()
+    }
+
+    fn clear(&mut self) {
+        // Empty the cells in place so those filled again keep their
+        // allocations, the rest are dropped once the rebuild is done
+        for points in self.cells.values_mut() {
+            points.clear();
+        }
+        self.bounds = None;
+    }
+
+    fn insert(&mut self, entity: Entity, position: Vec3) {
+        let cell = self.cell(position);
+        self.cells.entry(cell).or_default().push((entity, position));
+        self.bounds = Some(match self.bounds {
+            Some((min, max)) => (min.min(cell), max.max(cell)),
+            None => (cell, cell),
+        });
+    }
+}
+
+pub(crate) fn update_trail_spatial_index(
+    mut index: ResMut<TrailSpatialIndex>,
+    trail_query: Query<(Entity, &Trail), With<IndexedTrail>>,
+    frame_query: Query<&GlobalTransform>,
+) {
+    index.clear();
+    for (entity, trail) in trail_query.iter() {
+        // Points of trails with a reference frame are stored in its space
+        let frame = trail
+            .frame_entity()
+            .and_then(|e| frame_query.get(e).ok())
+            .map(GlobalTransform::affine);
+        for point in trail.points.iter() {
+            let position = match frame {
+                Some(frame) => frame.transform_point3(point.position),
+                None => point.position,
+            };
+            index.insert(entity, position);
+        }
+    }
+    index.cells.retain(|_, points| !points.is_empty());
+}
@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+
+use crate::slot::MeshSlot;
+use crate::{create_trail_mesh_2d, Trail};
+
+/// Draws the [`Trail`] on the same entity as a flat ribbon in the XY plane for
+/// `Camera2d` scenes, instead of the 3D mesh.
+///
+/// Points are recorded and expired by the [`Trail`] exactly as in 3D, from the
+/// X and Y of the entity's translation. The trail's `width` is in world units.
+#[derive(Component, Clone, Debug)]
+pub struct Trail2d {
+    /// Material handle for the ribbon
+    pub material: Handle<ColorMaterial>,
+    /// Z coordinate the ribbon is drawn at, for sorting against sprites
+    pub z: f32,
+    pub(crate) slot: MeshSlot,
+}
+
+impl Trail2d {
+    pub fn new(material: Handle<ColorMaterial>) -> Self {
+        Self {
+            material,
+            z: 0.0,
+            slot: MeshSlot::default(),
+        }
+    }
+}
+
+pub(crate) fn generate_trail_meshes_2d(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut trail_query: Query<(&Trail, &mut Trail2d)>,
+) {
+    for (trail, mut trail_2d) in trail_query.iter_mut() {
+        let trail_2d = &mut *trail_2d;
+        if !trail.render || trail.points.len() < 2 {
+            trail_2d.slot.clear(&mut commands, &mut meshes);
+            continue;
+        }
+
+        let points: Vec<Vec2> = trail
+            .render_points()
+            .iter()
+            .map(|p| p.position.xy())
+            .collect();
+        let mesh = create_trail_mesh_2d(&points, trail);
+        let transform = Transform::from_xyz(0.0, 0.0, trail_2d.z);
+
+        let mesh_entity = trail_2d
+            .slot
+            .show(&mut commands, &mut meshes, mesh, |handle| {
+                MaterialMesh2dBundle {
+                    mesh: Mesh2dHandle(handle),
+                    material: trail_2d.material.clone(),
+                    transform,
+                    ..default()
+                }
+            });
+        commands
+            .entity(mesh_entity)
+            .insert((trail_2d.material.clone(), transform));
+    }
+}
+
+/// Despawn a 2D trail's mesh entity along with its owner or the removed
+/// `Trail2d`
+pub(crate) fn despawn_removed_trail_2d_meshes(
+    trigger: Trigger<OnRemove, Trail2d>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut trail_query: Query<&mut Trail2d>,
+) {
+    if let Ok(mut trail_2d) = trail_query.get_mut(trigger.entity()) {
+        trail_2d.slot.clear(&mut commands, &mut meshes);
+    }
+}
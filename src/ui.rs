@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+use bevy::window::PrimaryWindow;
+
+use crate::slot::MeshSlot;
+use crate::{create_trail_mesh_2d, Trail};
+
+/// A trail recorded and drawn in screen space, for cursor trails and UI
+/// effects.
+///
+/// Positions are logical window pixels measured from the top left, matching
+/// UI node transforms and `Window::cursor_position`. The ribbon is drawn as a
+/// 2D mesh laid out for a `Camera2d` at the origin with the default
+/// projection; to draw it over a 3D scene, add such a camera with a higher
+/// `order` and `ClearColorConfig::None`.
+#[derive(Component)]
+pub struct UiTrail {
+    /// Maximum number of trail points to keep
+    pub max_points: usize,
+    /// How often to add new trail points (in seconds)
+    pub emit_rate: f32,
+    /// Width of the trail in logical pixels
+    pub width: f32,
+    /// Seconds a point lives for
+    pub max_age: f32,
+    /// Material handle for the trail
+    pub material: Handle<ColorMaterial>,
+    /// Screen position the trail follows. Kept in sync automatically for UI
+    /// nodes, set it yourself for anything else such as the cursor.
+    pub position: Vec2,
+    /// Records and expires the points like any other trail, in UI space
+    trail: Trail,
+    slot: MeshSlot,
+}
+
+impl UiTrail {
+    pub fn new(
+        max_points: usize,
+        emit_rate: f32,
+        width: f32,
+        material: Handle<ColorMaterial>,
+    ) -> Self {
+        Self {
+            max_points,
+            emit_rate,
+            width,
+            max_age: 5.0,
+            material,
+            position: Vec2::ZERO,
+            trail: Trail::new(max_points, emit_rate, width, Handle::default()),
+            slot: MeshSlot::default(),
+        }
+    }
+}
+
+pub(crate) fn update_ui_trails(
+    time: Res<Time>,
+    mut trail_query: Query<(&mut UiTrail, Option<&GlobalTransform>, Has<Node>)>,
+) {
+    for (mut ui_trail, transform, is_node) in trail_query.iter_mut() {
+        let ui_trail = &mut *ui_trail;
+        if let (Some(transform), true) = (transform, is_node) {
+            ui_trail.position = transform.translation().truncate();
+        }
+
+        let trail = &mut ui_trail.trail;
+        trail.max_points = ui_trail.max_points;
+        trail.max_age = Some(ui_trail.max_age);
+        trail.width = ui_trail.width;
+        if trail.emit_rate != ui_trail.emit_rate {
+            trail.set_emit_rate(ui_trail.emit_rate);
+            ui_trail.emit_rate = trail.emit_rate;
+        }
+
+        if trail.tick(time.delta()) {
+            let timestamp = trail.clock;
+            trail.push_point(ui_trail.position.extend(0.0), timestamp);
+        }
+        trail.expire_aged(1.0);
+    }
+}
+
+pub(crate) fn generate_ui_trail_meshes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut trail_query: Query<&mut UiTrail>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let half_size = window.size() * 0.5;
+
+    for mut ui_trail in trail_query.iter_mut() {
+        let ui_trail = &mut *ui_trail;
+        if ui_trail.trail.points.len() < 2 {
+            ui_trail.slot.clear(&mut commands, &mut meshes);
+            continue;
+        }
+
+        // UI space has its origin at the top left with Y pointing down
+        let points: Vec<Vec2> = ui_trail
+            .trail
+            .points
+            .iter()
+            .map(|p| Vec2::new(p.position.x - half_size.x, half_size.y - p.position.y))
+            .collect();
+        let mesh = create_trail_mesh_2d(&points, &ui_trail.trail);
+        ui_trail
+            .slot
+            .show(&mut commands, &mut meshes, mesh, |handle| {
+                MaterialMesh2dBundle {
+                    mesh: Mesh2dHandle(handle),
+                    material: ui_trail.material.clone(),
+                    ..default()
+                }
+            });
+    }
+}
+
+/// Despawn a UI trail's mesh entity along with its owner or the removed
+/// `UiTrail`
+pub(crate) fn despawn_removed_ui_trail_meshes(
+    trigger: Trigger<OnRemove, UiTrail>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut trail_query: Query<&mut UiTrail>,
+) {
+    if let Ok(mut trail) = trail_query.get_mut(trigger.entity()) {
+        trail.slot.clear(&mut commands, &mut meshes);
+    }
+}